@@ -1,110 +1,193 @@
 use bytes::{Buf, BufMut};
 
 pub const SIZEOF_U16: usize = std::mem::size_of::<u16>();
+pub const SIZEOF_U32: usize = std::mem::size_of::<u32>();
+
+/// Default number of entries between restart points.
+pub const DEFAULT_RESTART_INTERVAL: usize = 16;
+
+/// Length of the common prefix of two byte slices, capped at `u16::MAX`.
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    let max = a.len().min(b.len()).min(u16::MAX as usize);
+    let mut i = 0;
+    while i < max && a[i] == b[i] {
+        i += 1;
+    }
+    i
+}
 
 /// A data block in an SSTable.
+///
+/// Entries use LevelDB-style prefix compression: each entry stores the number
+/// of bytes it shares with the previous key, then only the non-shared suffix.
+/// Every `restart_interval` entries a "restart" entry stores the full key
+/// (`shared_len = 0`) and its byte offset is recorded so random access stays
+/// O(restart_interval) rather than O(entries).
 pub struct Block {
     data: Vec<u8>,
-    pub offsets: Vec<u16>,
+    restarts: Vec<u32>,
+    restart_interval: usize,
 }
 
 impl Block {
     /// Decode a byte vector into a Block.
     pub fn decode(data: &[u8]) -> Block {
-        let num_of_elements = (&data[data.len() - SIZEOF_U16..]).get_u16_le() as usize;
-        let data_end = data.len() - SIZEOF_U16 - num_of_elements * SIZEOF_U16;
-        let offsets_raw = &data[data_end..data.len() - SIZEOF_U16];
-        let offsets = offsets_raw
-            .chunks(SIZEOF_U16)
-            .map(|mut x| x.get_u16_le())
+        let n = data.len();
+        let restart_count = (&data[n - SIZEOF_U32..]).get_u32_le() as usize;
+        let restart_interval = (&data[n - 2 * SIZEOF_U32..n - SIZEOF_U32]).get_u32_le() as usize;
+        let restarts_end = n - 2 * SIZEOF_U32;
+        let restarts_start = restarts_end - restart_count * SIZEOF_U32;
+        let restarts = data[restarts_start..restarts_end]
+            .chunks(SIZEOF_U32)
+            .map(|mut x| x.get_u32_le())
             .collect();
-        let data = data[0..data_end].to_vec();
-        Block { data, offsets }
+        let data = data[0..restarts_start].to_vec();
+        Block {
+            data,
+            restarts,
+            restart_interval,
+        }
     }
 
     /// Encode the block data for storage.
     pub fn encode(&self) -> Vec<u8> {
         let mut buf = self.data.clone();
-        for offset in &self.offsets {
-            buf.put_u16_le(*offset);
+        for restart in &self.restarts {
+            buf.put_u32_le(*restart);
         }
-        buf.put_u16_le(self.offsets.len() as u16);
+        buf.put_u32_le(self.restart_interval as u32);
+        buf.put_u32_le(self.restarts.len() as u32);
         buf
     }
 
-    /// Get the key and value at the given index.
-    pub fn get_entry(&self, idx: usize) -> (&[u8], &[u8]) {
-        let start = self.offsets[idx] as usize;
-        let end = if idx + 1 < self.offsets.len() {
-            self.offsets[idx + 1] as usize
-        } else {
-            self.data.len()
-        };
-        let entry = &self.data[start..end];
-        let mut entry_mut = entry;
+    /// Parse the entry stored at byte offset `pos`, splicing `prev_key` with
+    /// the stored suffix to reconstruct the full key. Returns the key, the
+    /// value, and the offset of the following entry.
+    fn entry_at(&self, pos: usize, prev_key: &[u8]) -> (Vec<u8>, &[u8], usize) {
+        let mut cur = &self.data[pos..];
+        let shared = cur.get_u16_le() as usize;
+        let non_shared = cur.get_u16_le() as usize;
+        let value_len = cur.get_u16_le() as usize;
+        let header = 3 * SIZEOF_U16;
+        let key_start = pos + header;
+        let key_end = key_start + non_shared;
+        let value_end = key_end + value_len;
+
+        let mut key = Vec::with_capacity(shared + non_shared);
+        key.extend_from_slice(&prev_key[..shared]);
+        key.extend_from_slice(&self.data[key_start..key_end]);
+        let value = &self.data[key_end..value_end];
+        (key, value, value_end)
+    }
 
-        let key_len = entry_mut.get_u16_le() as usize;
-        let key = &entry[2..2 + key_len];
-        let value_len = (&entry[2 + key_len..]).get_u16_le() as usize;
-        let value = &entry[2 + key_len + 2..2 + key_len + 2 + value_len];
+    /// Get the key and value at the given index.
+    pub fn get_entry(&self, idx: usize) -> (Vec<u8>, &[u8]) {
+        let restart_idx = idx / self.restart_interval;
+        let mut pos = self.restarts[restart_idx] as usize;
+        let mut key = Vec::new();
+        let mut cur = restart_idx * self.restart_interval;
+        loop {
+            let (entry_key, value, next) = self.entry_at(pos, &key);
+            if cur == idx {
+                return (entry_key, value);
+            }
+            key = entry_key;
+            pos = next;
+            cur += 1;
+        }
+    }
 
-        (key, value)
+    /// Total number of entries in the block.
+    pub fn num_entries(&self) -> usize {
+        let mut pos = 0;
+        let mut key = Vec::new();
+        let mut count = 0;
+        while pos < self.data.len() {
+            let (entry_key, _, next) = self.entry_at(pos, &key);
+            key = entry_key;
+            pos = next;
+            count += 1;
+        }
+        count
     }
 }
 
 /// Builds a block with a target size.
 pub struct BlockBuilder {
-    offsets: Vec<u16>,
+    restarts: Vec<u32>,
     data: Vec<u8>,
     block_size: usize,
+    restart_interval: usize,
+    last_key: Vec<u8>,
+    counter: usize,
 }
 
 impl BlockBuilder {
     pub fn new(block_size: usize) -> Self {
+        Self::new_with_restart_interval(block_size, DEFAULT_RESTART_INTERVAL)
+    }
+
+    pub fn new_with_restart_interval(block_size: usize, restart_interval: usize) -> Self {
         Self {
-            offsets: Vec::new(),
+            restarts: Vec::new(),
             data: Vec::new(),
             block_size,
+            restart_interval: restart_interval.max(1),
+            last_key: Vec::new(),
+            counter: 0,
         }
     }
 
     /// Adds a key-value pair to the block. Returns false if the block is full.
     pub fn add(&mut self, key: &[u8], value: &[u8]) -> bool {
         assert!(!key.is_empty(), "key must not be empty");
-        // Check if adding this entry would exceed the target block size
-        // 2 (key_len) + key_len + 2 (val_len) + val_len
-        let entry_size = SIZEOF_U16 + key.len() + SIZEOF_U16 + value.len();
-        // SIZEOF_U16 (offset) + SIZEOF_U16 (num_entries)
-        let metadata_increase = SIZEOF_U16;
 
-        // Total size if we add this entry = current_data + current_offsets + new_entry + new_offset + num_entries_field
+        let is_restart = self.counter.is_multiple_of(self.restart_interval);
+        let shared = if is_restart {
+            0
+        } else {
+            common_prefix(&self.last_key, key)
+        };
+        let non_shared = key.len() - shared;
+
+        // 3 * u16 header + non_shared key suffix + value
+        let entry_size = 3 * SIZEOF_U16 + non_shared + value.len();
+        let restart_increase = if is_restart { SIZEOF_U32 } else { 0 };
         let total_size_after = self.data.len()
-            + (self.offsets.len() * SIZEOF_U16)
             + entry_size
-            + metadata_increase
-            + SIZEOF_U16;
+            + (self.restarts.len() * SIZEOF_U32)
+            + restart_increase
+            + 2 * SIZEOF_U32;
 
         if total_size_after > self.block_size && !self.is_empty() {
             return false;
         }
 
-        self.offsets.push(self.data.len() as u16);
-        self.data.put_u16_le(key.len() as u16);
-        self.data.put(key);
+        if is_restart {
+            self.restarts.push(self.data.len() as u32);
+        }
+
+        self.data.put_u16_le(shared as u16);
+        self.data.put_u16_le(non_shared as u16);
         self.data.put_u16_le(value.len() as u16);
+        self.data.put(&key[shared..]);
         self.data.put(value);
 
+        self.last_key = key.to_vec();
+        self.counter += 1;
+
         true
     }
 
     pub fn is_empty(&self) -> bool {
-        self.offsets.is_empty()
+        self.counter == 0
     }
 
     pub fn build(self) -> Block {
         Block {
             data: self.data,
-            offsets: self.offsets,
+            restarts: self.restarts,
+            restart_interval: self.restart_interval,
         }
     }
 }
@@ -122,8 +205,24 @@ mod tests {
         let encoded = block.encode();
         let decoded = Block::decode(&encoded);
 
-        assert_eq!(decoded.offsets.len(), 2);
-        assert_eq!(decoded.get_entry(0), (&b"key1"[..], &b"value1"[..]));
-        assert_eq!(decoded.get_entry(1), (&b"key2"[..], &b"value2"[..]));
+        assert_eq!(decoded.num_entries(), 2);
+        assert_eq!(decoded.get_entry(0), (b"key1".to_vec(), &b"value1"[..]));
+        assert_eq!(decoded.get_entry(1), (b"key2".to_vec(), &b"value2"[..]));
+    }
+
+    #[test]
+    fn test_prefix_compression_across_restarts() {
+        let mut builder = BlockBuilder::new_with_restart_interval(4096, 2);
+        let keys: Vec<String> = (0..5).map(|i| format!("prefixed_key_{i:02}")).collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(builder.add(key.as_bytes(), format!("v{i}").as_bytes()));
+        }
+        let decoded = Block::decode(&builder.build().encode());
+        assert_eq!(decoded.num_entries(), keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            let (k, v) = decoded.get_entry(i);
+            assert_eq!(k, key.as_bytes());
+            assert_eq!(v, format!("v{i}").as_bytes());
+        }
     }
 }
@@ -1,5 +1,11 @@
 use bytes::{BufMut, Bytes};
 
+use crate::sstable::{decode_block_frame, encode_block_frame, CompressionType};
+
+/// Filter blocks at or above this size are Snappy-compressed; smaller ones are
+/// stored raw. Both carry the one-byte codec tag and varint length prefix.
+const COMPRESS_THRESHOLD: usize = 256;
+
 /// A simple Bloom Filter implementation.
 pub struct Bloom {
     /// data of filter in bits
@@ -36,20 +42,32 @@ impl Bloom {
         }
     }
 
-    /// Decode a Bloom Filter from a byte buffer.
+    /// Decode a Bloom Filter from a byte buffer. The buffer carries the codec
+    /// tag written by [`Bloom::encode`], so a compressed filter is inflated
+    /// transparently here.
     pub fn decode(buf: &[u8]) -> crate::Result<Self> {
-        let filter = &buf[..buf.len() - 1];
-        let k = buf[buf.len() - 1];
+        let raw = decode_block_frame(buf)?;
+        let filter = &raw[..raw.len() - 1];
+        let k = raw[raw.len() - 1];
         Ok(Self {
             filter: Bytes::copy_from_slice(filter),
             k,
         })
     }
 
-    /// Encode the Bloom Filter to a byte buffer.
+    /// Encode the Bloom Filter to a byte buffer, prefixing a one-byte codec tag
+    /// so the reader can transparently decompress. Filters past
+    /// [`COMPRESS_THRESHOLD`] are stored Snappy-compressed.
     pub fn encode(&self, buf: &mut Vec<u8>) {
-        buf.extend_from_slice(&self.filter);
-        buf.put_u8(self.k);
+        let mut raw = Vec::with_capacity(self.filter.len() + 1);
+        raw.extend_from_slice(&self.filter);
+        raw.put_u8(self.k);
+        let codec = if raw.len() >= COMPRESS_THRESHOLD {
+            CompressionType::Snappy
+        } else {
+            CompressionType::None
+        };
+        buf.extend_from_slice(&encode_block_frame(codec, &raw));
     }
 
     /// Check if the key may exist in the Bloom Filter.
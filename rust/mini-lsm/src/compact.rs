@@ -0,0 +1,387 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use crate::error::Result;
+use crate::lsm_storage::{sst_path, LsmStorage};
+use crate::manifest::VersionEdit;
+use crate::sstable::{SsTable, SsTableBlockCursor, SsTableBuilder};
+
+/// Trigger a compaction once L0 holds at least this many tables.
+pub const L0_COMPACTION_TRIGGER: usize = 4;
+/// Max number of merged entries folded into one output table before it is
+/// sealed, bounding how long a single step holds data resident.
+pub const MAX_REINDEX_BATCH: usize = 8192;
+/// Target byte size of an output table when sealing by size.
+pub const TARGET_SST_SIZE: usize = 1 << 20;
+/// Block size for output tables.
+const SST_BLOCK_SIZE: usize = 4096;
+/// Size ratio between adjacent levels (leveled strategy).
+pub const LEVEL_SIZE_MULTIPLIER: usize = 10;
+
+/// A cursor over one input table's entries, ordered for the merge heap by
+/// `(key, priority)` so that on equal keys the higher-priority (newer) table
+/// is popped first and shadows the rest. Reads its table one block at a time
+/// via [`SsTableBlockCursor`] rather than materializing it up front, so a
+/// merge over many (or large) inputs only holds one decoded block per input
+/// resident at once.
+struct MergeCursor {
+    cursor: SsTableBlockCursor,
+    priority: usize,
+}
+
+impl MergeCursor {
+    fn key(&self) -> Vec<u8> {
+        self.cursor
+            .peek()
+            .expect("cursor is only queued while it has entries")
+            .0
+    }
+}
+
+impl PartialEq for MergeCursor {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for MergeCursor {}
+impl PartialOrd for MergeCursor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for MergeCursor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key()
+            .cmp(&other.key())
+            .then(self.priority.cmp(&other.priority))
+    }
+}
+
+/// Streaming k-way merge of the input tables (given in descending priority),
+/// yielding sorted, de-duplicated entries with shadowed keys collapsed one at
+/// a time. Tombstones are dropped only when `is_bottommost` is set; otherwise
+/// they are yielded so a key deleted here doesn't resurrect an older value
+/// still sitting in a deeper, non-participating level.
+///
+/// Only a handful of decoded blocks (one per still-active input) are ever
+/// resident at once; the merge never materializes an input table or the
+/// merged output in full.
+struct MergeIter {
+    heap: BinaryHeap<Reverse<MergeCursor>>,
+    is_bottommost: bool,
+    last_key: Option<Bytes>,
+}
+
+impl MergeIter {
+    fn new(inputs: &[Arc<SsTable>], is_bottommost: bool) -> Result<Self> {
+        let mut heap = BinaryHeap::new();
+        for (priority, table) in inputs.iter().enumerate() {
+            let cursor = SsTableBlockCursor::new(table.clone())?;
+            if cursor.peek().is_some() {
+                heap.push(Reverse(MergeCursor { cursor, priority }));
+            }
+        }
+        Ok(Self {
+            heap,
+            is_bottommost,
+            last_key: None,
+        })
+    }
+
+    fn next(&mut self) -> Result<Option<(Bytes, Bytes)>> {
+        loop {
+            let Some(Reverse(mut cursor)) = self.heap.pop() else {
+                return Ok(None);
+            };
+            let (key, value) = cursor
+                .cursor
+                .peek()
+                .expect("cursor is only queued while it has entries");
+            let key = Bytes::from(key);
+            cursor.cursor.advance()?;
+            if cursor.cursor.peek().is_some() {
+                self.heap.push(Reverse(cursor));
+            }
+
+            // The first pop for a key is the newest; keep it (unless a
+            // tombstone on the bottom-most level) and skip any older
+            // duplicates still sitting behind it in the heap.
+            if self.last_key.as_ref() == Some(&key) {
+                continue;
+            }
+            self.last_key = Some(key.clone());
+            if value.is_empty() && self.is_bottommost {
+                continue;
+            }
+            return Ok(Some((key, value)));
+        }
+    }
+}
+
+/// Byte budget for `level` under the leveled strategy: each level holds
+/// `LEVEL_SIZE_MULTIPLIER` times as much as the one above it. A level that
+/// outgrows its budget is merged down into the next.
+fn level_target_size(level: usize) -> u64 {
+    (TARGET_SST_SIZE as u64).saturating_mul((LEVEL_SIZE_MULTIPLIER as u64).saturating_pow(level as u32))
+}
+
+impl LsmStorage {
+    /// Run a compaction if L0 has grown past the trigger.
+    pub fn maybe_compact(&self) -> Result<()> {
+        if self.state.read().l0_sstables.len() >= L0_COMPACTION_TRIGGER {
+            self.force_compaction()?;
+        }
+        Ok(())
+    }
+
+    /// Drain L0 into L1, then cascade deeper while any level exceeds its
+    /// size-ratio budget, so the LSM stays shaped like a leveled tree rather
+    /// than piling everything into L1. The merge and table building run without
+    /// the write lock; it is only taken to swap in each new version.
+    pub fn force_compaction(&self) -> Result<()> {
+        let l0_ids = { self.state.read().l0_sstables.clone() };
+        if l0_ids.is_empty() {
+            return Ok(());
+        }
+
+        // L0 always folds into L1.
+        self.compact_into(&l0_ids, 1)?;
+
+        // Pick the next level/target by size ratio: push a level down whenever
+        // it has outgrown its budget for the current shape.
+        let mut level = 1;
+        loop {
+            let (ids, size) = {
+                let guard = self.state.read();
+                match guard.levels.iter().find(|(lvl, _)| *lvl == level) {
+                    Some((_, ids)) => {
+                        let size: u64 = ids
+                            .iter()
+                            .filter_map(|id| guard.sstables.get(id))
+                            .map(|t| t.size_bytes())
+                            .sum();
+                        (ids.clone(), size)
+                    }
+                    None => break,
+                }
+            };
+            if ids.is_empty() || size <= level_target_size(level) {
+                break;
+            }
+            self.compact_into(&ids, level + 1)?;
+            level += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Merge `source_ids` (newest-first) with the current contents of
+    /// `target_level` into fresh tables (sealed by `MAX_REINDEX_BATCH` entries
+    /// or `TARGET_SST_SIZE` bytes), dropping shadowed keys, and tombstones too
+    /// when `target_level` is the bottom-most level holding data. The version
+    /// edit is recorded before the write lock swaps in the result.
+    fn compact_into(&self, source_ids: &[usize], target_level: usize) -> Result<()> {
+        let (target_ids, tables, is_bottommost) = {
+            let guard = self.state.read();
+            let target = guard
+                .levels
+                .iter()
+                .find(|(lvl, _)| *lvl == target_level)
+                .map(|(_, ids)| ids.clone())
+                .unwrap_or_default();
+            // Bottom-most iff no deeper level currently holds any data: only
+            // then is it guaranteed that no older version of a key survives
+            // below this merge.
+            let is_bottommost = !guard
+                .levels
+                .iter()
+                .any(|(lvl, ids)| *lvl > target_level && !ids.is_empty());
+            (target, guard.sstables.clone(), is_bottommost)
+        };
+
+        // Descending priority: the source (newest) first, then the target.
+        let mut inputs: Vec<Arc<SsTable>> = Vec::new();
+        for id in source_ids.iter().chain(target_ids.iter()) {
+            if let Some(table) = tables.get(id) {
+                inputs.push(table.clone());
+            }
+        }
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        let merged = MergeIter::new(&inputs, is_bottommost)?;
+        let new_tables = self.seal_tables(merged)?;
+
+        let removed: Vec<usize> = source_ids.iter().chain(target_ids.iter()).copied().collect();
+        let added: Vec<usize> = new_tables.iter().map(|(id, _)| *id).collect();
+
+        // Record the edit before swapping so recovery sees the same version.
+        self.manifest.add_edit(&VersionEdit::Compaction {
+            level: target_level,
+            removed: removed.clone(),
+            added: added.clone(),
+        })?;
+
+        {
+            let mut guard = self.state.write();
+            guard.l0_sstables.retain(|id| !source_ids.contains(id));
+            // Drop the consumed source tables from whatever level held them.
+            for (lvl, ids) in guard.levels.iter_mut() {
+                if *lvl != target_level {
+                    ids.retain(|id| !removed.contains(id));
+                }
+            }
+            for id in &removed {
+                guard.sstables.remove(id);
+            }
+            for (id, table) in &new_tables {
+                guard.sstables.insert(*id, table.clone());
+            }
+            if let Some((_, ids)) = guard.levels.iter_mut().find(|(lvl, _)| *lvl == target_level) {
+                *ids = added.clone();
+            } else {
+                guard.levels.push((target_level, added.clone()));
+            }
+            guard.levels.sort_by_key(|(lvl, _)| *lvl);
+        }
+
+        Ok(())
+    }
+
+    /// Seal merged entries into output tables in bounded batches so a single
+    /// compaction never holds more than `MAX_REINDEX_BATCH` entries (or
+    /// `TARGET_SST_SIZE` bytes) resident at once. `merged` is pulled one entry
+    /// at a time, so peak memory is this batch plus one decoded block per
+    /// input table, not the whole compaction.
+    fn seal_tables(&self, mut merged: MergeIter) -> Result<Vec<(usize, Arc<SsTable>)>> {
+        let mut new_tables: Vec<(usize, Arc<SsTable>)> = Vec::new();
+        let mut builder = SsTableBuilder::new(SST_BLOCK_SIZE);
+        let mut count = 0usize;
+        let mut est = 0usize;
+        while let Some((key, value)) = merged.next()? {
+            builder.add(&key, &value)?;
+            count += 1;
+            est += key.len() + value.len();
+            if count >= MAX_REINDEX_BATCH || est >= TARGET_SST_SIZE {
+                let id = self.next_sst_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let table =
+                    builder.build(id, sst_path(&self.path, id), Some(self.block_cache.clone()))?;
+                new_tables.push((id, Arc::new(table)));
+                builder = SsTableBuilder::new(SST_BLOCK_SIZE);
+                count = 0;
+                est = 0;
+            }
+        }
+        if count > 0 {
+            let id = self.next_sst_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let table =
+                builder.build(id, sst_path(&self.path, id), Some(self.block_cache.clone()))?;
+            new_tables.push((id, Arc::new(table)));
+        }
+        Ok(new_tables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsm_storage::LsmStorage;
+    use crate::sstable::SsTableBuilder;
+    use tempfile::tempdir;
+
+    fn build_table(id: usize, dir: &std::path::Path, entries: &[(&[u8], &[u8])]) -> Arc<SsTable> {
+        let mut builder = SsTableBuilder::new(64);
+        for (key, value) in entries {
+            builder.add(key, value).expect("add");
+        }
+        let path = dir.join(format!("{id}.sst"));
+        Arc::new(builder.build(id, &path, None).expect("build"))
+    }
+
+    fn collect_merge(inputs: &[Arc<SsTable>], is_bottommost: bool) -> Vec<(Bytes, Bytes)> {
+        let mut iter = MergeIter::new(inputs, is_bottommost).expect("merge iter");
+        let mut out = Vec::new();
+        while let Some(entry) = iter.next().expect("next") {
+            out.push(entry);
+        }
+        out
+    }
+
+    #[test]
+    fn test_merge_carries_tombstone_unless_bottommost() {
+        let dir = tempdir().expect("tempdir");
+        // `newer` shadows `older`'s value for "k" with a tombstone; `older`
+        // is meant to stand in for a value still resident in a deeper,
+        // non-participating level.
+        let newer = build_table(1, dir.path(), &[(b"k", b"")]);
+        let older = build_table(2, dir.path(), &[(b"k", b"v1")]);
+
+        // Not the bottom-most level: the tombstone must survive so it keeps
+        // shadowing whatever is left at depth.
+        let merged = collect_merge(&[newer.clone(), older.clone()], false);
+        assert_eq!(merged, vec![(Bytes::from_static(b"k"), Bytes::new())]);
+
+        // Bottom-most level: nothing deeper can resurface, so the tombstone
+        // is safe to drop for good.
+        let merged = collect_merge(&[newer, older], true);
+        assert!(merged.is_empty());
+    }
+
+    #[test]
+    fn test_merge_streams_blocks_without_materializing_whole_tables() {
+        let dir = tempdir().expect("tempdir");
+        // Block size of 64 packs only a couple of small entries per block, so
+        // this table spans many blocks; the merge cursor must page through
+        // them rather than reading the table in one shot.
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = (0..500)
+            .map(|i| (format!("k{i:04}").into_bytes(), format!("v{i}").into_bytes()))
+            .collect();
+        let refs: Vec<(&[u8], &[u8])> = entries
+            .iter()
+            .map(|(k, v)| (k.as_slice(), v.as_slice()))
+            .collect();
+        let table = build_table(1, dir.path(), &refs);
+        assert!(table.block_meta.len() > 10);
+
+        let merged = collect_merge(&[table], true);
+        assert_eq!(merged.len(), entries.len());
+        assert_eq!(merged[0], (Bytes::from_static(b"k0000"), Bytes::from_static(b"v0")));
+        assert_eq!(
+            merged.last().unwrap(),
+            &(Bytes::from_static(b"k0499"), Bytes::from_static(b"v499"))
+        );
+    }
+
+    #[test]
+    fn test_compaction_merges_l0_and_drops_tombstones() {
+        let dir = tempdir().expect("tempdir");
+        let storage = LsmStorage::open(dir.path()).expect("open");
+
+        // Produce several L0 tables by flushing after each large batch.
+        let value = vec![b'y'; 128];
+        for round in 0..L0_COMPACTION_TRIGGER {
+            for i in 0..10_000 {
+                storage
+                    .put(format!("k{round}-{i:08}").as_bytes(), &value)
+                    .expect("put");
+            }
+            storage.force_flush_next_imm_memtable().expect("flush");
+        }
+
+        // After crossing the trigger, L0 should have been drained into L1.
+        {
+            let guard = storage.state.read();
+            assert!(guard.l0_sstables.len() < L0_COMPACTION_TRIGGER);
+            assert!(guard.levels.iter().any(|(lvl, ids)| *lvl == 1 && !ids.is_empty()));
+        }
+
+        assert_eq!(
+            storage.get(b"k0-00000000").unwrap(),
+            Some(Bytes::from(vec![b'y'; 128]))
+        );
+    }
+}
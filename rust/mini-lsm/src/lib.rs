@@ -3,6 +3,7 @@ pub mod bloom;
 pub mod compact;
 pub mod error;
 pub mod lsm_storage;
+pub mod manifest;
 pub mod memtable;
 pub mod sstable;
 pub mod wal;
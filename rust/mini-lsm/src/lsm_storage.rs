@@ -1,13 +1,17 @@
 use bytes::Bytes;
 use parking_lot::{Mutex, RwLock};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::error::Result;
+use crate::manifest::{Manifest, VersionEdit};
 use crate::memtable::MemTable;
-use crate::sstable::{BlockCache, SsTable};
+use crate::sstable::{BlockCache, SsTable, SsTableBuilder};
+
+/// Target SSTable block size used when flushing memtables.
+const SST_BLOCK_SIZE: usize = 4096;
 
 pub struct LsmStorageState {
     pub memtable: Arc<MemTable>,
@@ -30,10 +34,24 @@ impl LsmStorageState {
 }
 
 pub struct LsmStorage {
-    state: Arc<RwLock<LsmStorageState>>,
-    path: PathBuf,
-    block_cache: Arc<Mutex<BlockCache>>,
-    next_sst_id: AtomicUsize,
+    pub(crate) state: Arc<RwLock<LsmStorageState>>,
+    pub(crate) path: PathBuf,
+    pub(crate) block_cache: Arc<Mutex<BlockCache>>,
+    pub(crate) manifest: Manifest,
+    pub(crate) next_sst_id: AtomicUsize,
+}
+
+/// Path of the WAL backing the memtable with the given id.
+fn wal_path(base: &Path, id: usize) -> PathBuf {
+    if id == 0 {
+        base.join("mem.wal")
+    } else {
+        base.join(format!("{id:05}.wal"))
+    }
+}
+
+pub(crate) fn sst_path(base: &Path, id: usize) -> PathBuf {
+    base.join(format!("{id:05}.sst"))
 }
 
 impl LsmStorage {
@@ -41,40 +59,163 @@ impl LsmStorage {
         let path = path.as_ref().to_path_buf();
         std::fs::create_dir_all(&path)?;
 
-        // TODO: Load manifest/recovery
-        let memtable = Arc::new(MemTable::create_with_wal(0, path.join("mem.wal"))?);
-        let state = Arc::new(RwLock::new(LsmStorageState::create(memtable)));
         let block_cache = Arc::new(Mutex::new(lru::LruCache::new(
             std::num::NonZeroUsize::new(1024).unwrap(),
         )));
 
+        let manifest_path = path.join("MANIFEST");
+        let (manifest, edits) = Manifest::recover(&manifest_path)?;
+
+        // Replay the manifest to rebuild the on-disk version.
+        let mut memtable_ids = vec![0usize];
+        let mut flushed: HashSet<usize> = HashSet::new();
+        let mut l0_sstables: Vec<usize> = Vec::new();
+        let mut levels: Vec<(usize, Vec<usize>)> = Vec::new();
+        let mut sst_ids: HashSet<usize> = HashSet::new();
+        let mut max_id = 0usize;
+
+        for edit in &edits {
+            match edit {
+                VersionEdit::NewMemtable(id) => {
+                    memtable_ids.push(*id);
+                    max_id = max_id.max(*id);
+                }
+                VersionEdit::Flush(id) => {
+                    flushed.insert(*id);
+                    l0_sstables.insert(0, *id);
+                    sst_ids.insert(*id);
+                    max_id = max_id.max(*id);
+                }
+                VersionEdit::Compaction {
+                    level,
+                    removed,
+                    added,
+                } => {
+                    for id in removed {
+                        l0_sstables.retain(|x| x != id);
+                        for (_, ids) in levels.iter_mut() {
+                            ids.retain(|x| x != id);
+                        }
+                        sst_ids.remove(id);
+                    }
+                    if !levels.iter().any(|(lvl, _)| lvl == level) {
+                        levels.push((*level, Vec::new()));
+                    }
+                    let ids = &mut levels
+                        .iter_mut()
+                        .find(|(lvl, _)| lvl == level)
+                        .unwrap()
+                        .1;
+                    for id in added {
+                        ids.push(*id);
+                        sst_ids.insert(*id);
+                        max_id = max_id.max(*id);
+                    }
+                }
+            }
+        }
+
+        // Reopen every live SSTable.
+        let mut sstables: HashMap<usize, Arc<SsTable>> = HashMap::new();
+        for id in &sst_ids {
+            let table = SsTable::open(*id, sst_path(&path, *id), Some(block_cache.clone()))?;
+            sstables.insert(*id, Arc::new(table));
+        }
+
+        // Replay the WALs of any memtables not yet flushed, most-recent last.
+        let unflushed: Vec<usize> = memtable_ids
+            .iter()
+            .copied()
+            .filter(|id| !flushed.contains(id))
+            .collect();
+
+        let (memtable, imm_memtables, next_id) = if unflushed.is_empty() {
+            let new_id = max_id + 1;
+            manifest.add_edit(&VersionEdit::NewMemtable(new_id))?;
+            let memtable = MemTable::create_with_wal(new_id, wal_path(&path, new_id))?;
+            (Arc::new(memtable), Vec::new(), new_id + 1)
+        } else {
+            let (active_id, imm_ids) = unflushed.split_last().unwrap();
+            let memtable =
+                Arc::new(MemTable::recover_from_wal(*active_id, wal_path(&path, *active_id))?);
+            // Newest immutable memtable first.
+            let mut imm = Vec::with_capacity(imm_ids.len());
+            for id in imm_ids.iter().rev() {
+                imm.push(Arc::new(MemTable::recover_from_wal(*id, wal_path(&path, *id))?));
+            }
+            (memtable, imm, max_id.max(*active_id) + 1)
+        };
+
+        let mut state = LsmStorageState::create(memtable);
+        state.imm_memtables = imm_memtables;
+        state.l0_sstables = l0_sstables;
+        state.levels = levels;
+        state.sstables = sstables;
+
         Ok(Self {
-            state,
+            state: Arc::new(RwLock::new(state)),
             path,
             block_cache,
-            next_sst_id: AtomicUsize::new(1),
+            manifest,
+            next_sst_id: AtomicUsize::new(next_id),
         })
     }
 
     pub fn get(&self, key: &[u8]) -> Result<Option<Bytes>> {
-        let snapshot = {
+        let (memtable, imm_memtables, l0_sstables, levels, sstables) = {
             let guard = self.state.read();
-            guard.memtable.clone()
-        }; // Cheap clone Arc
+            (
+                guard.memtable.clone(),
+                guard.imm_memtables.clone(),
+                guard.l0_sstables.clone(),
+                guard.levels.clone(),
+                guard.sstables.clone(),
+            )
+        };
 
         // 1. Search MemTable
-        if let Some(value) = snapshot.get(key) {
+        if let Some(value) = memtable.get(key) {
             if value.is_empty() {
                 return Ok(None);
             } // Tombstone
             return Ok(Some(value));
         }
 
-        // 2. Search Immutable MemTables
-        // TODO: Add search logic
+        // 2. Search Immutable MemTables (newest first)
+        for memtable in &imm_memtables {
+            if let Some(value) = memtable.get(key) {
+                if value.is_empty() {
+                    return Ok(None);
+                }
+                return Ok(Some(value));
+            }
+        }
 
-        // 3. Search L0 SSTables
-        // TODO: Add search logic
+        // 3. Search L0 SSTables (newest first)
+        for id in &l0_sstables {
+            if let Some(table) = sstables.get(id) {
+                if let Some(value) = table.get(key)? {
+                    if value.is_empty() {
+                        return Ok(None);
+                    }
+                    return Ok(Some(value));
+                }
+            }
+        }
+
+        // 4. Search the leveled SSTables (shallowest level first)
+        for (_, ids) in &levels {
+            for id in ids {
+                if let Some(table) = sstables.get(id) {
+                    if let Some(value) = table.get(key)? {
+                        if value.is_empty() {
+                            return Ok(None);
+                        }
+                        return Ok(Some(value));
+                    }
+                }
+            }
+        }
 
         Ok(None)
     }
@@ -102,16 +243,91 @@ impl LsmStorage {
         }
 
         let old_memtable = guard.memtable.clone();
-        let new_id = old_memtable.id() + 1;
+        let new_id = self.next_sst_id.fetch_add(1, Ordering::SeqCst);
         let new_memtable = Arc::new(MemTable::create_with_wal(
             new_id,
-            self.path.join(format!("{:05}.wal", new_id)),
+            wal_path(&self.path, new_id),
         )?);
 
+        // Record the new memtable before exposing it so a crash after this
+        // point recovers the same recency order.
+        self.manifest.add_edit(&VersionEdit::NewMemtable(new_id))?;
+
         guard.imm_memtables.insert(0, old_memtable);
         guard.memtable = new_memtable;
 
-        // Trigger flush task here
         Ok(())
     }
+
+    /// Flush the oldest immutable memtable into an L0 SSTable.
+    pub fn force_flush_next_imm_memtable(&self) -> Result<()> {
+        let flush_memtable = {
+            let guard = self.state.read();
+            match guard.imm_memtables.last() {
+                Some(memtable) => memtable.clone(),
+                None => return Ok(()),
+            }
+        };
+
+        let sst_id = flush_memtable.id();
+        let mut builder = SsTableBuilder::new(SST_BLOCK_SIZE);
+        flush_memtable.flush(&mut builder)?;
+        let table = builder.build(
+            sst_id,
+            sst_path(&self.path, sst_id),
+            Some(self.block_cache.clone()),
+        )?;
+
+        self.manifest.add_edit(&VersionEdit::Flush(sst_id))?;
+
+        {
+            let mut guard = self.state.write();
+            guard.imm_memtables.pop();
+            guard.l0_sstables.insert(0, sst_id);
+            guard.sstables.insert(sst_id, Arc::new(table));
+        }
+
+        // A fresh L0 table may push us over the compaction trigger.
+        self.maybe_compact()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_recover_flushed_and_unflushed() {
+        let dir = tempdir().expect("tempdir");
+
+        {
+            let storage = LsmStorage::open(dir.path()).expect("open");
+            // Write enough data to trigger a freeze, then flush to L0.
+            let value = vec![b'x'; 128];
+            for i in 0..20_000 {
+                storage
+                    .put(format!("flushed{i:08}").as_bytes(), &value)
+                    .expect("put");
+            }
+            storage
+                .force_flush_next_imm_memtable()
+                .expect("flush imm memtable");
+            // This key stays in the active memtable's WAL only.
+            storage.put(b"unflushed", b"kept").expect("put unflushed");
+
+            assert_eq!(storage.get(b"unflushed").unwrap(), Some(Bytes::from("kept")));
+        }
+
+        // Reopen from disk and read back both a flushed key and the key that
+        // only ever lived in the WAL.
+        let storage = LsmStorage::open(dir.path()).expect("reopen");
+        assert_eq!(storage.get(b"unflushed").unwrap(), Some(Bytes::from("kept")));
+        assert_eq!(
+            storage.get(b"flushed00000000").unwrap(),
+            Some(Bytes::from(vec![b'x'; 128]))
+        );
+    }
 }
@@ -0,0 +1,100 @@
+use bytes::{Buf, BufMut};
+use crc32fast::Hasher;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::error::Result;
+
+/// A single mutation to the on-disk version, appended to the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VersionEdit {
+    /// A memtable was flushed into the L0 SSTable with the given id.
+    Flush(usize),
+    /// A fresh memtable (with its own WAL) was created.
+    NewMemtable(usize),
+    /// A compaction replaced `removed` tables at `level` with `added` ones.
+    Compaction {
+        level: usize,
+        removed: Vec<usize>,
+        added: Vec<usize>,
+    },
+}
+
+/// Append-only log of [`VersionEdit`]s used to recover the storage state on
+/// startup. Each record is `[len: u32][json][crc32: u32]`.
+pub struct Manifest {
+    file: Mutex<BufWriter<File>>,
+}
+
+impl Manifest {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self {
+            file: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    /// Replay an existing manifest, returning the recorded edits in order and a
+    /// handle positioned for further appends. Replay stops at the first torn or
+    /// checksum-mismatched tail record.
+    pub fn recover(path: impl AsRef<Path>) -> Result<(Self, Vec<VersionEdit>)> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).unwrap_or_default();
+        let mut edits = Vec::new();
+        let mut pos = 0;
+        while pos + 4 <= bytes.len() {
+            let len = (&bytes[pos..pos + 4]).get_u32_le() as usize;
+            let record_start = pos + 4;
+            let record_end = record_start + len;
+            let crc_end = record_end + 4;
+            if crc_end > bytes.len() {
+                break;
+            }
+            let record = &bytes[record_start..record_end];
+            let stored_crc = (&bytes[record_end..crc_end]).get_u32_le();
+            let mut hasher = Hasher::new();
+            hasher.update(record);
+            if hasher.finalize() != stored_crc {
+                break;
+            }
+            edits.push(serde_json::from_slice(record)?);
+            pos = crc_end;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok((
+            Self {
+                file: Mutex::new(BufWriter::new(file)),
+            },
+            edits,
+        ))
+    }
+
+    /// Durably append a version edit to the log.
+    pub fn add_edit(&self, edit: &VersionEdit) -> Result<()> {
+        let record = serde_json::to_vec(edit)?;
+        let mut hasher = Hasher::new();
+        hasher.update(&record);
+        let crc = hasher.finalize();
+
+        let mut buf = Vec::with_capacity(record.len() + 8);
+        buf.put_u32_le(record.len() as u32);
+        buf.extend_from_slice(&record);
+        buf.put_u32_le(crc);
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&buf)?;
+        file.flush()?;
+        file.get_mut().sync_all()?;
+        Ok(())
+    }
+}
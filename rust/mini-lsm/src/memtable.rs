@@ -32,6 +32,23 @@ impl MemTable {
         })
     }
 
+    /// Rebuild a MemTable by replaying the records of an existing WAL.
+    pub fn recover_from_wal(id: usize, path: impl AsRef<std::path::Path>) -> crate::Result<Self> {
+        let (wal, records) = crate::wal::Wal::recover(path)?;
+        let map = SkipMap::new();
+        let mut size = 0;
+        for (key, value) in records {
+            size += key.len() + value.len();
+            map.insert(key, value);
+        }
+        Ok(Self {
+            map,
+            wal: Some(wal),
+            id,
+            approximate_size: AtomicUsize::new(size),
+        })
+    }
+
     /// Get a value by key.
     pub fn get(&self, key: &[u8]) -> Option<Bytes> {
         self.map.get(key).map(|e| e.value().clone())
@@ -58,8 +75,11 @@ impl MemTable {
         unimplemented!("scan")
     }
 
-    pub fn flush(&self, _builder: &mut crate::sstable::SsTableBuilder) -> crate::Result<()> {
-        unimplemented!("flush to sstable")
+    pub fn flush(&self, builder: &mut crate::sstable::SsTableBuilder) -> crate::Result<()> {
+        for entry in self.map.iter() {
+            builder.add(entry.key(), entry.value())?;
+        }
+        Ok(())
     }
 
     pub fn id(&self) -> usize {
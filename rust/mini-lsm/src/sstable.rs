@@ -1,31 +1,228 @@
 use bytes::{Buf, BufMut, Bytes};
 use crc32fast::Hasher;
+use memmap2::Mmap;
 use parking_lot::Mutex;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::block::BlockBuilder;
+use crate::block::{Block, BlockBuilder};
 use crate::bloom::Bloom;
 use crate::error::{LsmError, Result};
 
-const FOOTER_SIZE: usize = 8 + 4 + 4 + 4;
+const FOOTER_SIZE: usize = 8 + 4 + 4 + 4 + 1 + 8 + 8 + 8 + 8;
+
+/// Per-block compression codec. Selected when constructing an
+/// [`SsTableBuilder`] and recorded in the [`Footer`] so a reader knows which
+/// decompressor to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionType {
+    None,
+    Lz4,
+    Snappy,
+}
+
+impl CompressionType {
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            CompressionType::None => 0,
+            CompressionType::Lz4 => 1,
+            CompressionType::Snappy => 2,
+        }
+    }
+
+    pub(crate) fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(CompressionType::None),
+            1 => Ok(CompressionType::Lz4),
+            2 => Ok(CompressionType::Snappy),
+            other => Err(LsmError::Format(format!(
+                "unknown compression tag: {other}"
+            ))),
+        }
+    }
+
+    pub(crate) fn compress(self, raw: &[u8]) -> Vec<u8> {
+        match self {
+            CompressionType::None => raw.to_vec(),
+            CompressionType::Lz4 => lz4_flex::compress(raw),
+            CompressionType::Snappy => snap::raw::Encoder::new()
+                .compress_vec(raw)
+                .expect("snappy compression is infallible for in-memory buffers"),
+        }
+    }
+
+    pub(crate) fn decompress(self, compressed: &[u8], uncompressed_len: usize) -> Result<Vec<u8>> {
+        match self {
+            CompressionType::None => Ok(compressed.to_vec()),
+            CompressionType::Lz4 => lz4_flex::decompress(compressed, uncompressed_len)
+                .map_err(|e| LsmError::Format(format!("lz4 decompress: {e}"))),
+            CompressionType::Snappy => snap::raw::Decoder::new()
+                .decompress_vec(compressed)
+                .map_err(|e| LsmError::Format(format!("snappy decompress: {e}"))),
+        }
+    }
+}
+
+/// Append an unsigned LEB128 varint to `buf`.
+fn put_uvarint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+/// Read an unsigned LEB128 varint, returning the value and bytes consumed.
+fn get_uvarint(buf: &[u8]) -> Result<(u64, usize)> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    for (i, &b) in buf.iter().enumerate() {
+        result |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Ok((result, i + 1));
+        }
+        shift += 7;
+    }
+    Err(LsmError::Format("truncated varint".to_string()))
+}
+
+/// Verify that `bytes` hash to `expected` with CRC32.
+fn verify_checksum(bytes: &[u8], expected: u32) -> Result<()> {
+    let mut hasher = Hasher::new();
+    hasher.update(bytes);
+    let actual = hasher.finalize();
+    if actual != expected {
+        return Err(LsmError::ChecksumMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+/// Encode a filter block: each partition's bloom bytes followed by its CRC32,
+/// then an index of `(data_offset, filter_offset, filter_len)` triples and the
+/// partition count.
+fn encode_filter_block(partitions: &[(u32, Bloom)]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    let mut index: Vec<(u32, u32, u32)> = Vec::with_capacity(partitions.len());
+    for (data_offset, bloom) in partitions {
+        let filter_offset = buf.len() as u32;
+        let mut fbytes = Vec::new();
+        bloom.encode(&mut fbytes);
+        let mut hasher = Hasher::new();
+        hasher.update(&fbytes);
+        let crc = hasher.finalize();
+        let filter_len = fbytes.len() as u32;
+        buf.extend_from_slice(&fbytes);
+        buf.put_u32_le(crc);
+        index.push((*data_offset, filter_offset, filter_len));
+    }
+    for (data_offset, filter_offset, filter_len) in &index {
+        buf.put_u32_le(*data_offset);
+        buf.put_u32_le(*filter_offset);
+        buf.put_u32_le(*filter_len);
+    }
+    buf.put_u32_le(index.len() as u32);
+    buf
+}
+
+/// Decode a filter block into per-partition blooms, verifying each partition's
+/// checksum.
+fn decode_filter_block(bytes: &[u8]) -> Result<Vec<(u32, Bloom)>> {
+    let n = bytes.len();
+    let count = (&bytes[n - 4..]).get_u32_le() as usize;
+    let index_start = n - 4 - count * 12;
+    let mut index = &bytes[index_start..n - 4];
+    let mut partitions = Vec::with_capacity(count);
+    for _ in 0..count {
+        let data_offset = index.get_u32_le();
+        let filter_offset = index.get_u32_le() as usize;
+        let filter_len = index.get_u32_le() as usize;
+        let fbytes = &bytes[filter_offset..filter_offset + filter_len];
+        let stored_crc = (&bytes[filter_offset + filter_len..filter_offset + filter_len + 4])
+            .get_u32_le();
+        verify_checksum(fbytes, stored_crc)?;
+        partitions.push((data_offset, Bloom::decode(fbytes)?));
+    }
+    Ok(partitions)
+}
+
+/// Test the bloom partition covering `block_offset`. Returns true (do not
+/// skip) when no partition covers the block.
+fn filter_may_contain(partitions: &[(u32, Bloom)], block_offset: u32, key: &[u8]) -> bool {
+    let mut chosen: Option<&Bloom> = None;
+    for (offset, bloom) in partitions {
+        if *offset <= block_offset {
+            chosen = Some(bloom);
+        } else {
+            break;
+        }
+    }
+    match chosen {
+        Some(bloom) => bloom.may_contain(key),
+        None => true,
+    }
+}
+
+/// Frame `raw` for on-disk storage: `[codec tag: u8][uncompressed_len: varint]
+/// [compressed body]`. The matching reader is [`decode_block_frame`].
+pub(crate) fn encode_block_frame(codec: CompressionType, raw: &[u8]) -> Vec<u8> {
+    let compressed = codec.compress(raw);
+    let mut buf = Vec::with_capacity(1 + 5 + compressed.len());
+    buf.push(codec.tag());
+    put_uvarint(&mut buf, raw.len() as u64);
+    buf.extend_from_slice(&compressed);
+    buf
+}
+
+/// Decode a framed block: a one-byte codec tag, a varint uncompressed length,
+/// then the (possibly compressed) block bytes. Returns the raw block bytes
+/// ready for [`crate::block::Block::decode`].
+pub(crate) fn decode_block_frame(frame: &[u8]) -> Result<Vec<u8>> {
+    if frame.is_empty() {
+        return Err(LsmError::Format("empty block frame".to_string()));
+    }
+    let codec = CompressionType::from_tag(frame[0])?;
+    let (uncompressed_len, consumed) = get_uvarint(&frame[1..])?;
+    let body = &frame[1 + consumed..];
+    codec.decompress(body, uncompressed_len as usize)
+}
 
 pub(crate) struct Footer {
     file_size: u64,
     data_checksum: u32,
     index_checksum: u32,
     bloom_checksum: u32,
+    compression: u8,
+    index_offset: u64,
+    index_len: u64,
+    bloom_offset: u64,
+    bloom_len: u64,
 }
 
 impl Footer {
-    fn new(file_size: u64, data_checksum: u32, index_checksum: u32, bloom_checksum: u32) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        file_size: u64,
+        data_checksum: u32,
+        index_checksum: u32,
+        bloom_checksum: u32,
+        compression: u8,
+        index_offset: u64,
+        index_len: u64,
+        bloom_offset: u64,
+        bloom_len: u64,
+    ) -> Self {
         Self {
             file_size,
             data_checksum,
             index_checksum,
             bloom_checksum,
+            compression,
+            index_offset,
+            index_len,
+            bloom_offset,
+            bloom_len,
         }
     }
 
@@ -35,10 +232,14 @@ impl Footer {
         buf.put_u32_le(self.data_checksum);
         buf.put_u32_le(self.index_checksum);
         buf.put_u32_le(self.bloom_checksum);
+        buf.put_u8(self.compression);
+        buf.put_u64_le(self.index_offset);
+        buf.put_u64_le(self.index_len);
+        buf.put_u64_le(self.bloom_offset);
+        buf.put_u64_le(self.bloom_len);
         buf
     }
 
-    #[allow(dead_code)]
     fn decode(mut buf: &[u8]) -> Result<Self> {
         if buf.len() != FOOTER_SIZE {
             return Err(LsmError::Format("invalid footer length".to_string()));
@@ -48,6 +249,11 @@ impl Footer {
             data_checksum: buf.get_u32_le(),
             index_checksum: buf.get_u32_le(),
             bloom_checksum: buf.get_u32_le(),
+            compression: buf.get_u8(),
+            index_offset: buf.get_u64_le(),
+            index_len: buf.get_u64_le(),
+            bloom_offset: buf.get_u64_le(),
+            bloom_len: buf.get_u64_le(),
         })
     }
 }
@@ -70,27 +276,233 @@ pub struct SsTable {
     pub bloom_len: u64,
     #[allow(dead_code)]
     pub(crate) footer: Footer,
+    pub compression: CompressionType,
+    /// One bloom per data-block partition, keyed by the partition's first
+    /// data-block offset and sorted ascending.
+    pub(crate) filters: Vec<(u32, Bloom)>,
+    pub(crate) mmap: Option<Arc<Mmap>>,
     pub block_cache: Option<Arc<Mutex<BlockCache>>>,
 }
 
+impl SsTable {
+    /// Open an existing SSTable file by `id`, memory-mapping it and rebuilding
+    /// the in-memory index and bloom filter from the trailing footer.
+    pub fn open(
+        id: usize,
+        path: impl AsRef<Path>,
+        cache: Option<Arc<Mutex<BlockCache>>>,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let len = mmap.len();
+        if len < FOOTER_SIZE {
+            return Err(LsmError::Format("sstable smaller than footer".to_string()));
+        }
+
+        let footer = Footer::decode(&mmap[len - FOOTER_SIZE..])?;
+        let compression = CompressionType::from_tag(footer.compression)?;
+
+        let index_offset = footer.index_offset;
+        let index_len = footer.index_len;
+        let bloom_offset = footer.bloom_offset;
+        let bloom_len = footer.bloom_len;
+
+        // Verify each region against the checksum recorded in the footer.
+        verify_checksum(&mmap[..index_offset as usize], footer.data_checksum)?;
+        let index_bytes =
+            &mmap[index_offset as usize..(index_offset + index_len) as usize];
+        verify_checksum(index_bytes, footer.index_checksum)?;
+
+        let index_block = Block::decode(index_bytes);
+        let mut block_meta = Vec::with_capacity(index_block.num_entries());
+        for idx in 0..index_block.num_entries() {
+            let (first_key, offset_bytes) = index_block.get_entry(idx);
+            let offset = (&offset_bytes[..]).get_u32_le();
+            block_meta.push(BlockMeta { offset, first_key });
+        }
+
+        let bloom_bytes = &mmap[bloom_offset as usize..(bloom_offset + bloom_len) as usize];
+        verify_checksum(bloom_bytes, footer.bloom_checksum)?;
+        let filters = decode_filter_block(bloom_bytes)?;
+
+        Ok(SsTable {
+            id,
+            file_path: path,
+            block_meta,
+            index_offset,
+            index_len,
+            bloom_offset,
+            bloom_len,
+            footer,
+            compression,
+            filters,
+            mmap: Some(Arc::new(mmap)),
+            block_cache: cache,
+        })
+    }
+
+    /// On-disk size of the table in bytes, as recorded in the footer. Used by
+    /// the leveled compaction picker to compare a level against its budget.
+    pub fn size_bytes(&self) -> u64 {
+        self.footer.file_size
+    }
+
+    /// Decode the data block at `idx`, serving decompressed bytes from the
+    /// block cache (keyed by block offset) when present so repeated reads skip
+    /// both the mmap slice and decompression.
+    pub(crate) fn read_block(&self, idx: usize) -> Result<Block> {
+        let offset = self.block_meta[idx].offset;
+
+        if let Some(cache) = &self.block_cache {
+            if let Some(bytes) = cache.lock().get(&offset) {
+                return Ok(Block::decode(bytes));
+            }
+        }
+
+        let mmap = self
+            .mmap
+            .as_ref()
+            .ok_or_else(|| LsmError::Format("sstable has no backing mmap".to_string()))?;
+        let start = offset as usize;
+        let end = if idx + 1 < self.block_meta.len() {
+            self.block_meta[idx + 1].offset as usize
+        } else {
+            self.index_offset as usize
+        };
+        let raw = decode_block_frame(&mmap[start..end])?;
+
+        if let Some(cache) = &self.block_cache {
+            cache.lock().put(offset, Bytes::copy_from_slice(&raw));
+        }
+        Ok(Block::decode(&raw))
+    }
+
+    /// Look up `key` in this table, returning its (possibly empty, i.e.
+    /// tombstone) value if present. Binary-searches `block_meta` for the
+    /// candidate block, tests only the filter partition covering it, then
+    /// reads at most one data block.
+    pub fn get(&self, key: &[u8]) -> Result<Option<Bytes>> {
+        if self.block_meta.is_empty() {
+            return Ok(None);
+        }
+
+        // The candidate block is the last one whose first_key <= key.
+        let idx = match self
+            .block_meta
+            .binary_search_by(|meta| meta.first_key.as_slice().cmp(key))
+        {
+            Ok(i) => i,
+            Err(0) => return Ok(None),
+            Err(i) => i - 1,
+        };
+
+        // Test only the filter partition covering this block.
+        let block_offset = self.block_meta[idx].offset;
+        if !filter_may_contain(&self.filters, block_offset, key) {
+            return Ok(None);
+        }
+
+        let block = self.read_block(idx)?;
+        for i in 0..block.num_entries() {
+            let (k, v) = block.get_entry(i);
+            if k == key {
+                return Ok(Some(Bytes::copy_from_slice(v)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Streams a table's entries one data block at a time, rather than
+/// materializing the whole table up front. Used by the compaction merge
+/// cursor so a k-way merge over many (possibly large) input tables only
+/// holds one decoded block per input resident at once.
+pub struct SsTableBlockCursor {
+    table: Arc<SsTable>,
+    block_idx: usize,
+    block: Option<Block>,
+    entry_idx: usize,
+}
+
+impl SsTableBlockCursor {
+    pub fn new(table: Arc<SsTable>) -> Result<Self> {
+        let mut cursor = Self {
+            table,
+            block_idx: 0,
+            block: None,
+            entry_idx: 0,
+        };
+        cursor.load_block()?;
+        Ok(cursor)
+    }
+
+    fn load_block(&mut self) -> Result<()> {
+        self.block = if self.block_idx < self.table.block_meta.len() {
+            Some(self.table.read_block(self.block_idx)?)
+        } else {
+            None
+        };
+        self.entry_idx = 0;
+        Ok(())
+    }
+
+    /// The entry the cursor is currently positioned at, or `None` once every
+    /// block has been consumed.
+    pub fn peek(&self) -> Option<(Vec<u8>, Bytes)> {
+        let block = self.block.as_ref()?;
+        let (key, value) = block.get_entry(self.entry_idx);
+        Some((key, Bytes::copy_from_slice(value)))
+    }
+
+    /// Move past the current entry, decoding the next block if the current
+    /// one is exhausted.
+    pub fn advance(&mut self) -> Result<()> {
+        let Some(block) = &self.block else {
+            return Ok(());
+        };
+        self.entry_idx += 1;
+        if self.entry_idx >= block.num_entries() {
+            self.block_idx += 1;
+            self.load_block()?;
+        }
+        Ok(())
+    }
+}
+
+/// Data accumulated per ~2KB filter partition while building.
+const FILTER_PARTITION_SIZE: usize = 2048;
+
 pub struct SsTableBuilder {
     block_size: usize,
+    compression: CompressionType,
     current_block: BlockBuilder,
     current_first_key: Option<Vec<u8>>,
     block_meta: Vec<BlockMeta>,
     data: Vec<u8>,
-    keys: Vec<Bytes>,
+    current_block_keys: Vec<Bytes>,
+    partition_first_offset: Option<u32>,
+    partition_keys: Vec<Bytes>,
+    filters: Vec<(u32, Bloom)>,
 }
 
 impl SsTableBuilder {
     pub fn new(block_size: usize) -> Self {
+        Self::new_with_compression(block_size, CompressionType::None)
+    }
+
+    pub fn new_with_compression(block_size: usize, compression: CompressionType) -> Self {
         Self {
             block_size,
+            compression,
             current_block: BlockBuilder::new(block_size),
             current_first_key: None,
             block_meta: Vec::new(),
             data: Vec::new(),
-            keys: Vec::new(),
+            current_block_keys: Vec::new(),
+            partition_first_offset: None,
+            partition_keys: Vec::new(),
+            filters: Vec::new(),
         }
     }
 
@@ -106,10 +518,19 @@ impl SsTableBuilder {
                 return Err(LsmError::Format("entry too large for block".to_string()));
             }
         }
-        self.keys.push(Bytes::copy_from_slice(key));
+        self.current_block_keys.push(Bytes::copy_from_slice(key));
         Ok(())
     }
 
+    /// Seal the in-progress filter partition into a bloom covering its keys.
+    fn seal_filter_partition(&mut self) {
+        if let Some(offset) = self.partition_first_offset.take() {
+            let bloom = Bloom::build_from_keys(&self.partition_keys, 10);
+            self.filters.push((offset, bloom));
+            self.partition_keys.clear();
+        }
+    }
+
     pub fn build(
         mut self,
         id: usize,
@@ -117,6 +538,8 @@ impl SsTableBuilder {
         cache: Option<Arc<Mutex<BlockCache>>>,
     ) -> Result<SsTable> {
         self.finish_data_blocks()?;
+        // Seal any keys left in the final (sub-boundary) partition.
+        self.seal_filter_partition();
 
         let mut data_hasher = Hasher::new();
         data_hasher.update(&self.data);
@@ -127,9 +550,9 @@ impl SsTableBuilder {
         index_hasher.update(&index_bytes);
         let index_checksum = index_hasher.finalize();
 
-        let bloom = Bloom::build_from_keys(&self.keys, 10);
-        let mut bloom_bytes = Vec::new();
-        bloom.encode(&mut bloom_bytes);
+        // The filter region holds one bloom per data-block partition plus an
+        // index mapping each partition's first data offset to its bloom.
+        let bloom_bytes = encode_filter_block(&self.filters);
         let mut bloom_hasher = Hasher::new();
         bloom_hasher.update(&bloom_bytes);
         let bloom_checksum = bloom_hasher.finalize();
@@ -144,6 +567,11 @@ impl SsTableBuilder {
             data_checksum,
             index_checksum,
             bloom_checksum,
+            self.compression.tag(),
+            index_offset,
+            index_len,
+            bloom_offset,
+            bloom_len,
         );
 
         let path = path.as_ref();
@@ -153,6 +581,12 @@ impl SsTableBuilder {
         writer.write_all(&bloom_bytes)?;
         writer.write_all(&footer.encode())?;
         writer.flush()?;
+        drop(writer);
+
+        // Back the freshly written table with an mmap so it is immediately
+        // readable; `read_block` reads exclusively from the mapping.
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
 
         Ok(SsTable {
             id,
@@ -163,6 +597,9 @@ impl SsTableBuilder {
             bloom_offset,
             bloom_len,
             footer,
+            compression: self.compression,
+            filters: self.filters,
+            mmap: Some(Arc::new(mmap)),
             block_cache: cache,
         })
     }
@@ -186,13 +623,29 @@ impl SsTableBuilder {
             std::mem::replace(&mut self.current_block, BlockBuilder::new(self.block_size)).build();
         let encoded = block.encode();
         let offset = self.data.len() as u32;
-        self.data.extend_from_slice(&encoded);
+        if self.partition_first_offset.is_none() {
+            self.partition_first_offset = Some(offset);
+        }
+        self.partition_keys.append(&mut self.current_block_keys);
+        // Frame: [codec tag: u8][uncompressed_len: varint][compressed block].
+        let compressed = self.compression.compress(&encoded);
+        self.data.push(self.compression.tag());
+        put_uvarint(&mut self.data, encoded.len() as u64);
+        self.data.extend_from_slice(&compressed);
         self.block_meta.push(BlockMeta { offset, first_key });
+
+        // Emit a filter once the partition has grown past the boundary.
+        let partition_start = self.partition_first_offset.unwrap() as usize;
+        if self.data.len() - partition_start >= FILTER_PARTITION_SIZE {
+            self.seal_filter_partition();
+        }
         Ok(())
     }
 
     fn build_index_block(&self) -> Result<Vec<u8>> {
-        let mut index_builder = BlockBuilder::new(self.block_size);
+        // The index holds one entry per data block and must never be split, so
+        // it is built unbounded rather than capped at the data `block_size`.
+        let mut index_builder = BlockBuilder::new(usize::MAX);
         for meta in &self.block_meta {
             let mut offset_bytes = [0u8; 4];
             offset_bytes.as_mut().put_u32_le(meta.offset);
@@ -214,13 +667,53 @@ mod tests {
 
     #[test]
     fn test_sstable_footer_round_trip() {
-        let footer = Footer::new(123, 1, 2, 3);
+        let footer = Footer::new(123, 1, 2, 3, 2, 10, 20, 30, 40);
         let encoded = footer.encode();
         let decoded = Footer::decode(&encoded).expect("footer decode");
         assert_eq!(decoded.file_size, 123);
         assert_eq!(decoded.data_checksum, 1);
         assert_eq!(decoded.index_checksum, 2);
         assert_eq!(decoded.bloom_checksum, 3);
+        assert_eq!(decoded.compression, 2);
+        assert_eq!(decoded.index_offset, 10);
+        assert_eq!(decoded.index_len, 20);
+        assert_eq!(decoded.bloom_offset, 30);
+        assert_eq!(decoded.bloom_len, 40);
+    }
+
+    #[test]
+    fn test_block_frame_round_trip() {
+        let mut builder = SsTableBuilder::new_with_compression(64, CompressionType::Snappy);
+        builder.add(b"apple", b"1").expect("add apple");
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("2.sst");
+        let table = builder.build(2, &path, None).expect("build sstable");
+        assert_eq!(table.compression, CompressionType::Snappy);
+
+        let mut file = File::open(&path).expect("open sstable");
+        let mut frame = vec![0u8; table.index_offset as usize];
+        file.read_exact(&mut frame).expect("read data");
+        let raw = decode_block_frame(&frame).expect("decode frame");
+        let block = Block::decode(&raw);
+        assert_eq!(block.get_entry(0), (b"apple".to_vec(), &b"1"[..]));
+    }
+
+    #[test]
+    fn test_open_and_get() {
+        let mut builder = SsTableBuilder::new(64);
+        for i in 0..50 {
+            builder
+                .add(format!("k{i:04}").as_bytes(), format!("v{i}").as_bytes())
+                .expect("add");
+        }
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("3.sst");
+        builder.build(3, &path, None).expect("build");
+
+        let table = SsTable::open(3, &path, None).expect("open");
+        assert_eq!(table.get(b"k0000").unwrap(), Some(Bytes::from("v0")));
+        assert_eq!(table.get(b"k0049").unwrap(), Some(Bytes::from("v49")));
+        assert_eq!(table.get(b"missing").unwrap(), None);
     }
 
     #[test]
@@ -234,6 +727,23 @@ mod tests {
         assert!(decoded.may_contain(b"beta"));
     }
 
+    #[test]
+    fn test_large_bloom_compresses_and_round_trips() {
+        let keys: Vec<Bytes> = (0..1000)
+            .map(|i| Bytes::from(format!("key{i:06}")))
+            .collect();
+        let bloom = Bloom::build_from_keys(&keys, 10);
+        let mut encoded = Vec::new();
+        bloom.encode(&mut encoded);
+        // A filter this size clears the threshold, so the Snappy codec tag
+        // should be recorded rather than the `None` tag.
+        assert_eq!(encoded[0], CompressionType::Snappy.tag());
+        let decoded = Bloom::decode(&encoded).expect("bloom decode");
+        for key in &keys {
+            assert!(decoded.may_contain(key));
+        }
+    }
+
     #[test]
     fn test_builder_writes_index_and_footer() {
         let mut builder = SsTableBuilder::new(64);
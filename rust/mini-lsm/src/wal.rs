@@ -1,41 +1,376 @@
-use bytes::BufMut;
+use bytes::{Buf, BufMut, Bytes};
 use crc32fast::Hasher;
-use std::fs::File;
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
 use std::io::BufWriter;
 use std::io::Write;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::error::LsmError;
+use crate::sstable::CompressionType;
+
+/// Values at or above this many bytes are stored Snappy-compressed in the WAL;
+/// smaller records aren't worth the codec overhead and the fallback copy.
+const COMPRESS_THRESHOLD: usize = 256;
+
+/// Tuning for the group-commit writer: a batch is synced once it reaches
+/// `batch_size` buffered records or `flush_interval` has elapsed since the last
+/// sync, whichever comes first. Both bounds keep fsync cost amortized across
+/// concurrent writers without letting a low-traffic log go unsynced for long.
+#[derive(Debug, Clone, Copy)]
+pub struct WalOptions {
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+}
+
+impl Default for WalOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 64,
+            flush_interval: Duration::from_millis(10),
+        }
+    }
+}
+
+struct WalInner {
+    writer: BufWriter<File>,
+    /// Records appended since the last `sync_all`.
+    pending: usize,
+    last_sync: Instant,
+}
+
+/// State shared between a [`Wal`] and its background flusher thread.
+struct WalShared {
+    inner: Mutex<WalInner>,
+    options: WalOptions,
+    /// Set by `Wal::drop` to wake and retire the flusher thread.
+    stop: Mutex<bool>,
+    stop_cv: Condvar,
+}
 
 pub struct Wal {
-    file: Arc<Mutex<BufWriter<File>>>,
+    shared: Arc<WalShared>,
+    /// Joined on drop so the flusher never outlives its `File`.
+    flusher: Option<JoinHandle<()>>,
 }
 
 impl Wal {
     pub fn create(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::create_with_options(path, WalOptions::default())
+    }
+
+    pub fn create_with_options(
+        path: impl AsRef<Path>,
+        options: WalOptions,
+    ) -> crate::Result<Self> {
         let file = File::create(path)?;
-        Ok(Self {
-            file: Arc::new(Mutex::new(BufWriter::new(file))),
-        })
+        Ok(Self::from_file(file, options))
+    }
+
+    /// Open an existing WAL for appending without replaying it. Callers that
+    /// also need the recovered records should use [`Wal::recover`].
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::open_with_options(path, WalOptions::default())
+    }
+
+    pub fn open_with_options(
+        path: impl AsRef<Path>,
+        options: WalOptions,
+    ) -> crate::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::from_file(file, options))
+    }
+
+    fn from_file(file: File, options: WalOptions) -> Self {
+        let shared = Arc::new(WalShared {
+            inner: Mutex::new(WalInner {
+                writer: BufWriter::new(file),
+                pending: 0,
+                last_sync: Instant::now(),
+            }),
+            options,
+            stop: Mutex::new(false),
+            stop_cv: Condvar::new(),
+        });
+
+        let flusher = {
+            let shared = shared.clone();
+            std::thread::spawn(move || Self::run_flusher(shared))
+        };
+
+        Self {
+            shared,
+            flusher: Some(flusher),
+        }
+    }
+
+    /// Background half of group-commit: without this, a log that goes quiet
+    /// between writes would leave its tail sitting unsynced in the
+    /// `BufWriter` forever, since nothing but `put` used to check the
+    /// elapsed-time bound. Wakes every `flush_interval` (or immediately on
+    /// `drop`) and syncs whatever is pending if enough time has passed since
+    /// the last sync.
+    fn run_flusher(shared: Arc<WalShared>) {
+        let mut stop = shared.stop.lock().unwrap();
+        loop {
+            let (guard, _) = shared
+                .stop_cv
+                .wait_timeout(stop, shared.options.flush_interval)
+                .unwrap();
+            stop = guard;
+            if *stop {
+                return;
+            }
+
+            let mut inner = shared.inner.lock().unwrap();
+            if inner.pending > 0 && inner.last_sync.elapsed() >= shared.options.flush_interval {
+                let _ = Self::sync_locked(&mut inner);
+            }
+        }
+    }
+
+    /// Sequentially read the
+    /// `[codec u8][key_len u16][key][val_len u16][value][crc32 u32]` records of
+    /// a WAL, recomputing the checksum of each. A record whose bytes are fully
+    /// present but whose checksum does not match is corruption and yields
+    /// [`LsmError::ChecksumMismatch`]; a record truncated by a crashed write is
+    /// treated as a torn tail and stops the replay cleanly. The leading codec
+    /// tag names the compressor applied to the stored value, so a Snappy record
+    /// is inflated transparently here.
+    pub fn replay(path: impl AsRef<Path>) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        let bytes = std::fs::read(path.as_ref()).unwrap_or_default();
+        let mut records = Vec::new();
+        let mut pos = 0;
+        while pos + 3 <= bytes.len() {
+            let codec_tag = bytes[pos];
+            let key_len = (&bytes[pos + 1..pos + 3]).get_u16_le() as usize;
+            let val_len_at = pos + 3 + key_len;
+            if val_len_at + 2 > bytes.len() {
+                break;
+            }
+            let val_len = (&bytes[val_len_at..val_len_at + 2]).get_u16_le() as usize;
+            let value_start = val_len_at + 2;
+            let value_end = value_start + val_len;
+            let crc_end = value_end + 4;
+            if crc_end > bytes.len() {
+                // Torn tail: the final record was only partially written.
+                break;
+            }
+            let stored_crc = (&bytes[value_end..crc_end]).get_u32_le();
+            let mut hasher = Hasher::new();
+            hasher.update(&bytes[pos..value_end]);
+            let actual = hasher.finalize();
+            if actual != stored_crc {
+                return Err(LsmError::ChecksumMismatch {
+                    expected: stored_crc,
+                    actual,
+                });
+            }
+            // The checksum has vouched for the bytes; now decode the codec tag
+            // and inflate the value if it was stored compressed.
+            let codec = CompressionType::from_tag(codec_tag)?;
+            let value = codec.decompress(&bytes[value_start..value_end], 0)?;
+            records.push((
+                Bytes::copy_from_slice(&bytes[pos + 3..pos + 3 + key_len]),
+                Bytes::from(value),
+            ));
+            pos = crc_end;
+        }
+        Ok(records)
+    }
+
+    /// Replay an existing WAL and reopen it for appending so a memtable can be
+    /// rebuilt on startup.
+    pub fn recover(path: impl AsRef<Path>) -> crate::Result<(Self, Vec<(Bytes, Bytes)>)> {
+        let records = Self::replay(path.as_ref())?;
+        let wal = Self::open(path)?;
+        Ok((wal, records))
     }
 
     pub fn put(&self, key: &[u8], value: &[u8]) -> crate::Result<()> {
-        let mut file = self.file.lock().unwrap();
-        let mut buf: Vec<u8> = Vec::with_capacity(key.len() + value.len() + 8);
+        let mut inner = self.shared.inner.lock().unwrap();
+        let mut buf: Vec<u8> = Vec::with_capacity(key.len() + value.len() + 9);
 
-        // Format: [key_len: u16] [key] [val_len: u16] [value] [checksum: u32]
+        // Snappy-compress large values, falling back to the raw bytes whenever
+        // compression fails to shrink them (so the val_len stays u16-bounded).
+        let (codec, stored_value): (CompressionType, Cow<[u8]>) =
+            if value.len() >= COMPRESS_THRESHOLD {
+                let compressed = CompressionType::Snappy.compress(value);
+                if compressed.len() < value.len() {
+                    (CompressionType::Snappy, Cow::Owned(compressed))
+                } else {
+                    (CompressionType::None, Cow::Borrowed(value))
+                }
+            } else {
+                (CompressionType::None, Cow::Borrowed(value))
+            };
+
+        // Format: [codec: u8] [key_len: u16] [key] [val_len: u16] [value] [checksum: u32]
+        buf.push(codec.tag());
         buf.put_u16_le(key.len() as u16);
         buf.put(key);
-        buf.put_u16_le(value.len() as u16);
-        buf.put(value);
+        buf.put_u16_le(stored_value.len() as u16);
+        buf.put(stored_value.as_ref());
 
         let mut hasher = Hasher::new();
         hasher.update(&buf);
         let checksum = hasher.finalize();
         buf.put_u32_le(checksum);
 
-        file.write_all(&buf)?;
-        // Ideally we fsync here or periodically
-        // file.get_mut().sync_all()?;
+        inner.writer.write_all(&buf)?;
+        inner.pending += 1;
+
+        // Group-commit: sync once enough writers have piled up to make the
+        // fsync worth amortizing. The time bound that caps how stale an
+        // unsynced tail can get is enforced by the background flusher
+        // thread instead of here — checking it on every `put` would trip on
+        // every single write whenever traffic is sparser than
+        // `flush_interval`, defeating the batching this is meant to provide.
+        if inner.pending >= self.shared.options.batch_size {
+            Self::sync_locked(&mut inner)?;
+        }
         Ok(())
     }
+
+    /// Force every buffered record to durable storage and return once the
+    /// `sync_all` has completed, so a caller knows its write survived a crash.
+    pub fn sync(&self) -> crate::Result<()> {
+        let mut inner = self.shared.inner.lock().unwrap();
+        Self::sync_locked(&mut inner)
+    }
+
+    fn sync_locked(inner: &mut WalInner) -> crate::Result<()> {
+        inner.writer.flush()?;
+        inner.writer.get_ref().sync_all()?;
+        inner.pending = 0;
+        inner.last_sync = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for Wal {
+    /// Retire the background flusher so it never outlives the `File` it
+    /// flushes. Any already-pending records are left for the next `sync`
+    /// (or the next open's replay) exactly as before this background thread
+    /// existed — dropping a `Wal` was never an implicit sync point.
+    fn drop(&mut self) {
+        *self.shared.stop.lock().unwrap() = true;
+        self.shared.stop_cv.notify_one();
+        if let Some(flusher) = self.flusher.take() {
+            let _ = flusher.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_background_flusher_syncs_a_quiet_log() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("mem.wal");
+
+        // A batch_size far above one write means only the time bound can
+        // trigger a sync here, and nothing ever calls `put` again.
+        let options = WalOptions {
+            batch_size: 1000,
+            flush_interval: Duration::from_millis(20),
+        };
+        let wal = Wal::create_with_options(&path, options).expect("create");
+        wal.put(b"alpha", b"1").expect("put");
+
+        std::thread::sleep(Duration::from_millis(200));
+
+        // Read through a fresh handle: a `BufWriter` only makes bytes visible
+        // here once it has actually been flushed, so this only passes if the
+        // background flusher synced without a second `put`.
+        let on_disk = std::fs::read(&path).expect("read");
+        assert!(!on_disk.is_empty(), "quiescent log was never flushed");
+    }
+
+    #[test]
+    fn test_replay_round_trip_and_torn_tail() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("mem.wal");
+
+        {
+            let wal = Wal::create(&path).expect("create");
+            wal.put(b"alpha", b"1").expect("put");
+            wal.put(b"beta", b"2").expect("put");
+            wal.sync().expect("sync");
+        }
+
+        let records = Wal::replay(&path).expect("replay");
+        assert_eq!(
+            records,
+            vec![
+                (Bytes::from_static(b"alpha"), Bytes::from_static(b"1")),
+                (Bytes::from_static(b"beta"), Bytes::from_static(b"2")),
+            ]
+        );
+
+        // Append a few bytes that cannot form a complete record; replay should
+        // stop at the torn tail and keep the intact prefix.
+        {
+            let wal = Wal::open(&path).expect("open");
+            wal.put(b"gamma", b"3").expect("put");
+            wal.sync().expect("sync");
+        }
+        let mut raw = std::fs::read(&path).expect("read");
+        raw.truncate(raw.len() - 2);
+        std::fs::write(&path, &raw).expect("write");
+
+        let records = Wal::replay(&path).expect("replay");
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn test_large_value_compresses_and_round_trips() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("mem.wal");
+
+        // A highly compressible value past the threshold should shrink on disk
+        // yet replay back byte-for-byte.
+        let value = vec![b'z'; 4096];
+        {
+            let wal = Wal::create(&path).expect("create");
+            wal.put(b"big", &value).expect("put");
+            wal.sync().expect("sync");
+        }
+
+        let on_disk = std::fs::metadata(&path).expect("metadata").len() as usize;
+        assert!(on_disk < value.len(), "expected the record to be compressed");
+
+        let records = Wal::replay(&path).expect("replay");
+        assert_eq!(records, vec![(Bytes::from_static(b"big"), Bytes::from(value))]);
+    }
+
+    #[test]
+    fn test_replay_detects_corruption() {
+        let dir = tempdir().expect("tempdir");
+        let path = dir.path().join("mem.wal");
+
+        {
+            let wal = Wal::create(&path).expect("create");
+            wal.put(b"key", b"value").expect("put");
+            wal.sync().expect("sync");
+        }
+
+        // Flip a byte inside the value, leaving the record length intact so the
+        // checksum — not a torn tail — is what fails.
+        let mut raw = std::fs::read(&path).expect("read");
+        let idx = raw.len() - 5;
+        raw[idx] ^= 0xff;
+        std::fs::write(&path, &raw).expect("write");
+
+        assert!(matches!(
+            Wal::replay(&path),
+            Err(LsmError::ChecksumMismatch { .. })
+        ));
+    }
 }
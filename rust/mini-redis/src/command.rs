@@ -2,8 +2,8 @@ use crate::{
     db::Db, 
     error::MiniRedisError, 
     Result, 
-    frame::{Frame, FrameOwned}, 
-    connection::Connection
+    frame::{Frame, FrameOwned},
+    connection::FrameSink
 };
 use bytes::Bytes;
 use std::time::Duration;
@@ -15,6 +15,7 @@ pub enum Command {
     Publish(String, Bytes),
     Subscribe(Vec<String>),
     Ping(Option<String>),
+    Hello(Option<u8>),
 }
 
 impl Command {
@@ -91,6 +92,15 @@ impl Command {
                         });
                         Ok(Command::Ping(msg))
                     }
+                    "hello" => {
+                        let version = it.next().and_then(|f| match f {
+                            Frame::BulkString(Some(b)) => {
+                                std::str::from_utf8(b).ok().and_then(|s| s.parse::<u8>().ok())
+                            }
+                            _ => None,
+                        });
+                        Ok(Command::Hello(version))
+                    }
                     _ => Err(MiniRedisError::Protocol(format!("unknown command: {}", cmd_name))),
                 }
             }
@@ -171,6 +181,15 @@ impl Command {
                         });
                         Ok(Command::Ping(msg))
                     }
+                    "hello" => {
+                        let version = it.next().and_then(|f| match f {
+                            FrameOwned::BulkString(Some(b)) => {
+                                std::str::from_utf8(&b).ok().and_then(|s| s.parse::<u8>().ok())
+                            }
+                            _ => None,
+                        });
+                        Ok(Command::Hello(version))
+                    }
                     _ => Err(MiniRedisError::Protocol(format!("unknown command: {}", cmd_name))),
                 }
             }
@@ -178,14 +197,16 @@ impl Command {
         }
     }
 
-    pub async fn apply(self, db: &Db, conn: &mut Connection) -> Result<()> {
+    pub async fn apply<S: FrameSink>(self, db: &Db, conn: &mut S) -> Result<()> {
         match self {
             Command::Get(key) => {
-                let response = match db.get(&key) {
-                    Some(val) => FrameOwned::BulkString(Some(val)),
-                    None => FrameOwned::BulkString(None),
-                };
-                conn.write_frame(&response).await?;
+                match db.get(&key) {
+                    // Large values go out through the streaming bulk path so a
+                    // multi-megabyte GET never forces the whole value through an
+                    // intermediate frame buffer.
+                    Some(val) => conn.write_bulk_value(&val).await?,
+                    None => conn.write_frame(&FrameOwned::BulkString(None)).await?,
+                }
             }
             Command::Set(key, val, expire) => {
                 db.set(key, val, expire);
@@ -240,6 +261,30 @@ impl Command {
                 };
                 conn.write_frame(&response).await?;
             }
+            Command::Hello(version) => {
+                let requested = version.unwrap_or(conn.protocol());
+                if requested != crate::connection::RESP2 && requested != crate::connection::RESP3 {
+                    let response = FrameOwned::Error(format!(
+                        "NOPROTO unsupported protocol version {requested}"
+                    ));
+                    conn.write_frame(&response).await?;
+                } else {
+                    conn.set_protocol(requested);
+                    // Advertise the active protocol as a map; a RESP2 client
+                    // receives it flattened into an array.
+                    let info = FrameOwned::Map(vec![
+                        (
+                            FrameOwned::BulkString(Some(Bytes::from_static(b"server"))),
+                            FrameOwned::BulkString(Some(Bytes::from_static(b"mini-redis"))),
+                        ),
+                        (
+                            FrameOwned::BulkString(Some(Bytes::from_static(b"proto"))),
+                            FrameOwned::Integer(requested as i64),
+                        ),
+                    ]);
+                    conn.write_frame(&info).await?;
+                }
+            }
         }
         Ok(())
     }
@@ -1,98 +1,774 @@
-use crate::{Result, MiniRedisError, frame::FrameOwned, parse};
-use bytes::{BytesMut, Buf};
+use crate::{frame::FrameOwned, parse, MiniRedisError, Result};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::codec::{Decoder, Encoder, Framed};
 
+/// Chunk size used when streaming a bulk body to/from the socket.
+pub const BULK_CHUNK_SIZE: usize = 16 * 1024;
+/// Bulk values at least this large are streamed rather than buffered whole.
+pub const DEFAULT_BULK_STREAM_THRESHOLD: usize = 64 * 1024;
+
+/// Protocol version spoken on a connection. `HELLO 3` upgrades a client to
+/// RESP3; every connection starts at RESP2.
+pub const RESP2: u8 = 2;
+pub const RESP3: u8 = 3;
+
+/// A [`tokio_util`] codec that (de)serializes RESP frames. Decoding defers to
+/// `parse::check`/`parse::parse`; encoding writes the same bytes the old
+/// `write_frame` loop produced, downgrading RESP3-only frames when the peer
+/// still speaks RESP2.
+pub struct RespCodec {
+    protocol: u8,
+}
+
+impl RespCodec {
+    fn new() -> Self {
+        Self { protocol: RESP2 }
+    }
+}
+
+impl Decoder for RespCodec {
+    type Item = FrameOwned;
+    type Error = MiniRedisError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<FrameOwned>> {
+        match parse::check(src) {
+            Ok(()) => {
+                let (frame, consumed) = parse::parse(src)?;
+                let owned = FrameOwned::from(frame);
+                src.advance(consumed);
+                Ok(Some(owned))
+            }
+            Err(MiniRedisError::Incomplete) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Encoder<FrameOwned> for RespCodec {
+    type Error = MiniRedisError;
+
+    fn encode(&mut self, item: FrameOwned, dst: &mut BytesMut) -> Result<()> {
+        encode_frame(&item, dst, self.protocol);
+        Ok(())
+    }
+}
+
+/// Serialize a frame into `dst`. When `protocol` is [`RESP2`], the RESP3-only
+/// types are rendered as their closest RESP2 equivalent so a legacy client
+/// never sees a wire type it cannot parse.
+fn encode_frame(frame: &FrameOwned, dst: &mut BytesMut, protocol: u8) {
+    match frame {
+        FrameOwned::SimpleString(s) => {
+            dst.put_u8(b'+');
+            dst.put_slice(s.as_bytes());
+            dst.put_slice(b"\r\n");
+        }
+        FrameOwned::Error(s) => {
+            dst.put_u8(b'-');
+            dst.put_slice(s.as_bytes());
+            dst.put_slice(b"\r\n");
+        }
+        FrameOwned::Integer(i) => {
+            dst.put_u8(b':');
+            dst.put_slice(i.to_string().as_bytes());
+            dst.put_slice(b"\r\n");
+        }
+        FrameOwned::BulkString(None) => {
+            dst.put_slice(b"$-1\r\n");
+        }
+        FrameOwned::BulkString(Some(data)) => {
+            encode_bulk(dst, data);
+        }
+        FrameOwned::Array(None) => {
+            dst.put_slice(b"*-1\r\n");
+        }
+        FrameOwned::Array(Some(frames)) => {
+            encode_seq(dst, b'*', frames, protocol);
+        }
+        FrameOwned::Null => {
+            if protocol >= RESP3 {
+                dst.put_slice(b"_\r\n");
+            } else {
+                dst.put_slice(b"$-1\r\n");
+            }
+        }
+        FrameOwned::Boolean(b) => {
+            if protocol >= RESP3 {
+                dst.put_slice(if *b { b"#t\r\n" } else { b"#f\r\n" });
+            } else {
+                dst.put_slice(if *b { b":1\r\n" } else { b":0\r\n" });
+            }
+        }
+        FrameOwned::Double(d) => {
+            let text = format_double(*d);
+            if protocol >= RESP3 {
+                dst.put_u8(b',');
+                dst.put_slice(text.as_bytes());
+                dst.put_slice(b"\r\n");
+            } else {
+                encode_bulk(dst, text.as_bytes());
+            }
+        }
+        FrameOwned::BigNumber(s) => {
+            if protocol >= RESP3 {
+                dst.put_u8(b'(');
+                dst.put_slice(s.as_bytes());
+                dst.put_slice(b"\r\n");
+            } else {
+                encode_bulk(dst, s.as_bytes());
+            }
+        }
+        FrameOwned::VerbatimString(format, data) => {
+            if protocol >= RESP3 {
+                let len = format.len() + 1 + data.len();
+                dst.put_u8(b'=');
+                dst.put_slice(len.to_string().as_bytes());
+                dst.put_slice(b"\r\n");
+                dst.put_slice(format.as_bytes());
+                dst.put_u8(b':');
+                dst.put_slice(data);
+                dst.put_slice(b"\r\n");
+            } else {
+                encode_bulk(dst, data);
+            }
+        }
+        FrameOwned::Map(pairs) => {
+            encode_pairs(dst, b'%', pairs, protocol);
+        }
+        FrameOwned::Attribute(pairs) => {
+            // RESP2 has no attribute type; drop the metadata entirely.
+            if protocol >= RESP3 {
+                encode_pairs(dst, b'|', pairs, protocol);
+            }
+        }
+        FrameOwned::Set(items) => {
+            encode_seq(dst, if protocol >= RESP3 { b'~' } else { b'*' }, items, protocol);
+        }
+        FrameOwned::Push(items) => {
+            encode_seq(dst, if protocol >= RESP3 { b'>' } else { b'*' }, items, protocol);
+        }
+    }
+}
+
+fn encode_bulk(dst: &mut BytesMut, data: &[u8]) {
+    dst.put_u8(b'$');
+    dst.put_slice(data.len().to_string().as_bytes());
+    dst.put_slice(b"\r\n");
+    dst.put_slice(data);
+    dst.put_slice(b"\r\n");
+}
+
+fn encode_seq(dst: &mut BytesMut, tag: u8, frames: &[FrameOwned], protocol: u8) {
+    dst.put_u8(tag);
+    dst.put_slice(frames.len().to_string().as_bytes());
+    dst.put_slice(b"\r\n");
+    for frame in frames {
+        encode_frame(frame, dst, protocol);
+    }
+}
+
+fn encode_pairs(dst: &mut BytesMut, tag: u8, pairs: &[(FrameOwned, FrameOwned)], protocol: u8) {
+    if protocol >= RESP3 {
+        dst.put_u8(tag);
+        dst.put_slice(pairs.len().to_string().as_bytes());
+        dst.put_slice(b"\r\n");
+        for (key, value) in pairs {
+            encode_frame(key, dst, protocol);
+            encode_frame(value, dst, protocol);
+        }
+    } else {
+        // Flatten to a RESP2 array of alternating key/value entries.
+        dst.put_u8(b'*');
+        dst.put_slice((pairs.len() * 2).to_string().as_bytes());
+        dst.put_slice(b"\r\n");
+        for (key, value) in pairs {
+            encode_frame(key, dst, protocol);
+            encode_frame(value, dst, protocol);
+        }
+    }
+}
+
+/// Render a double the way RESP3 expects: bare `inf`/`-inf`/`nan` for the
+/// non-finite cases, otherwise the shortest round-tripping decimal.
+fn format_double(d: f64) -> String {
+    if d.is_infinite() {
+        if d > 0.0 { "inf".to_string() } else { "-inf".to_string() }
+    } else if d.is_nan() {
+        "nan".to_string()
+    } else {
+        d.to_string()
+    }
+}
+
+/// The transport operations command handling depends on, shared by the TCP
+/// [`Connection`] and the WebSocket [`WsConnection`] so `Command::apply` does
+/// not care which wire a client arrived on.
+///
+/// The transport types are concrete, so the `async fn` futures stay `Send`
+/// enough to spawn; the lint only matters for `dyn`/unconstrained callers.
+#[allow(async_fn_in_trait)]
+pub trait FrameSink {
+    /// Encode and send a single frame.
+    async fn write_frame(&mut self, frame: &FrameOwned) -> Result<()>;
+    /// Send a bulk string value, using a bounded-memory streaming path when the
+    /// transport supports it and the value is large enough to be worth it. The
+    /// default buffers the whole value into one frame; transports that can write
+    /// raw bytes to the socket override this to stream in chunks.
+    async fn write_bulk_value(&mut self, value: &[u8]) -> Result<()> {
+        self.write_frame(&FrameOwned::BulkString(Some(Bytes::copy_from_slice(value))))
+            .await
+    }
+    /// The protocol version currently in force.
+    fn protocol(&self) -> u8;
+    /// Switch the active protocol version (e.g. after a `HELLO 3`).
+    fn set_protocol(&mut self, version: u8);
+}
+
+/// A framed RESP connection. Wraps a [`Framed`] so callers also get access to
+/// the underlying `Stream`/`Sink` for composition with the tokio ecosystem.
 pub struct Connection {
-    stream: BufWriter<TcpStream>,
-    buffer: BytesMut,
+    framed: Framed<TcpStream, RespCodec>,
+    bulk_stream_threshold: usize,
 }
 
 impl Connection {
     pub fn new(socket: TcpStream) -> Self {
         Self {
-            stream: BufWriter::new(socket),
-            buffer: BytesMut::with_capacity(4096),
+            framed: Framed::new(socket, RespCodec::new()),
+            bulk_stream_threshold: DEFAULT_BULK_STREAM_THRESHOLD,
         }
     }
 
+    /// Read the next frame, returning `None` at a clean end of stream.
     pub async fn read_frame(&mut self) -> Result<Option<FrameOwned>> {
-        loop {
-            if let Some(frame) = self.parse_frame()? {
-                return Ok(Some(frame));
+        self.framed.next().await.transpose()
+    }
+
+    pub async fn write_frame(&mut self, frame: &FrameOwned) -> Result<()> {
+        self.framed.send(frame.clone()).await
+    }
+
+    /// Consume the connection, yielding the raw `Framed` transport so callers
+    /// can use `StreamExt`/`SinkExt` directly (timeouts, backpressure, ...).
+    pub fn into_framed(self) -> Framed<TcpStream, RespCodec> {
+        self.framed
+    }
+
+    /// The protocol version currently in force on this connection.
+    pub fn protocol(&self) -> u8 {
+        self.framed.codec().protocol
+    }
+
+    /// Switch the connection to `version` (e.g. after a `HELLO 3`). Subsequent
+    /// frames are encoded for that version.
+    pub fn set_protocol(&mut self, version: u8) {
+        self.framed.codec_mut().protocol = version;
+    }
+
+    /// Bulk values whose declared length is at least this many bytes should be
+    /// moved with the streaming helpers below rather than decoded into a
+    /// [`FrameOwned`], keeping per-connection memory bounded.
+    pub fn bulk_stream_threshold(&self) -> usize {
+        self.bulk_stream_threshold
+    }
+
+    pub fn set_bulk_stream_threshold(&mut self, threshold: usize) {
+        self.bulk_stream_threshold = threshold;
+    }
+
+    /// Whether a bulk of `len` bytes should be streamed instead of buffered.
+    pub fn should_stream_bulk(&self, len: usize) -> bool {
+        len >= self.bulk_stream_threshold
+    }
+
+    /// Write a bulk string whose body is pulled from `reader` in bounded
+    /// chunks, never holding more than [`BULK_CHUNK_SIZE`] bytes of the value
+    /// resident. The caller is responsible for supplying exactly `len` bytes.
+    pub async fn write_bulk_stream<R>(&mut self, reader: &mut R, len: usize) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+    {
+        // Drain anything the codec has queued so the raw bytes we are about to
+        // write land in order behind it.
+        self.framed.flush().await?;
+        let socket = self.framed.get_mut();
+
+        let mut header = Vec::with_capacity(16);
+        header.push(b'$');
+        header.extend_from_slice(len.to_string().as_bytes());
+        header.extend_from_slice(b"\r\n");
+        socket.write_all(&header).await?;
+
+        let mut buf = vec![0u8; BULK_CHUNK_SIZE];
+        let mut remaining = len;
+        while remaining > 0 {
+            let want = remaining.min(BULK_CHUNK_SIZE);
+            reader.read_exact(&mut buf[..want]).await?;
+            socket.write_all(&buf[..want]).await?;
+            remaining -= want;
+        }
+
+        socket.write_all(b"\r\n").await?;
+        socket.flush().await?;
+        Ok(())
+    }
+
+    /// Read one bulk string straight off the socket, forwarding its body to
+    /// `out` in [`BULK_CHUNK_SIZE`] chunks. Bytes the codec had already buffered
+    /// are consumed first, and any trailing bytes past the value (including the
+    /// `\r\n` terminator) are fed back into the codec so subsequent
+    /// [`read_frame`](Self::read_frame) calls see an intact stream.
+    ///
+    /// Returns the body length, or `None` for a RESP null bulk (`$-1`).
+    pub async fn read_bulk_stream<W>(&mut self, out: &mut W) -> Result<Option<usize>>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        // Take ownership of whatever the codec has buffered so we can read from
+        // the socket without aliasing it; unconsumed bytes are restored below.
+        let mut buf = self.framed.read_buffer_mut().split();
+
+        // Pull until the `$<len>\r\n` header is complete.
+        let (len, consumed) = loop {
+            match parse_bulk_header(&buf)? {
+                Some(header) => break header,
+                None => {
+                    let n = self.framed.get_mut().read_buf(&mut buf).await?;
+                    if n == 0 {
+                        return Err(MiniRedisError::ConnectionReset);
+                    }
+                }
             }
+        };
+        buf.advance(consumed);
 
-            if self.stream.read_buf(&mut self.buffer).await? == 0 {
-                if self.buffer.is_empty() {
-                    return Ok(None);
-                } else {
+        let len = match len {
+            Some(len) => len,
+            None => {
+                // Null bulk: nothing to stream, hand the leftovers back.
+                self.restore(buf);
+                return Ok(None);
+            }
+        };
+
+        self.read_bulk_body(&mut buf, len, out).await?;
+        self.restore(buf);
+        Ok(Some(len))
+    }
+
+    /// Forward `len` bytes of a bulk body from `buf` (topping up from the
+    /// socket as needed) into `out`, then consume the trailing `\r\n`
+    /// terminator, which may straddle a chunk boundary. Each top-up reserves
+    /// at most [`BULK_CHUNK_SIZE`] bytes of fresh capacity before reading, so
+    /// a single socket read can't balloon `buf` past one chunk regardless of
+    /// how much the peer has in flight.
+    async fn read_bulk_body<W>(&mut self, buf: &mut BytesMut, len: usize, out: &mut W) -> Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut remaining = len + 2;
+        let mut written = 0usize;
+        while written < len {
+            if buf.is_empty() {
+                buf.reserve(BULK_CHUNK_SIZE.min(len - written + 2));
+                let n = self.framed.get_mut().read_buf(buf).await?;
+                if n == 0 {
                     return Err(MiniRedisError::ConnectionReset);
                 }
             }
+            let take = buf.len().min(len - written);
+            out.write_all(&buf[..take]).await?;
+            buf.advance(take);
+            written += take;
+            remaining -= take;
         }
+        out.flush().await?;
+
+        while remaining > 0 {
+            if buf.is_empty() {
+                buf.reserve(BULK_CHUNK_SIZE.min(remaining));
+                let n = self.framed.get_mut().read_buf(buf).await?;
+                if n == 0 {
+                    return Err(MiniRedisError::ConnectionReset);
+                }
+            }
+            let take = buf.len().min(remaining);
+            buf.advance(take);
+            remaining -= take;
+        }
+        Ok(())
     }
 
-    fn parse_frame(&mut self) -> Result<Option<FrameOwned>> {
-        let (frame, consumed) = {
-            let buf = &self.buffer[..];
-            match parse::check(buf) {
-                Ok(_) => {
-                    let (frame, consumed) = parse::parse(buf)?;
-                    (Some(FrameOwned::from(frame)), consumed)
+    /// Read the next command frame as a `FrameOwned::Array` of bulk strings —
+    /// the shape every client request takes. A bulk argument declared at or
+    /// above [`bulk_stream_threshold`](Self::bulk_stream_threshold) is pulled
+    /// off the socket in [`BULK_CHUNK_SIZE`] steps via [`read_bulk_body`]
+    /// rather than waiting for [`RespCodec::decode`] to see the whole thing
+    /// buffered at once. This bounds the *transient* memory of the transfer —
+    /// no single socket read balloons past one chunk — but the argument is
+    /// still reassembled into one contiguous `Bytes` before the command is
+    /// applied, since [`Command::Set`](crate::command::Command::Set) hands it
+    /// to [`Db`](crate::db::Db), which stores whole values; that final
+    /// residency is a property of the in-memory store, not a gap in this
+    /// read path. Anything that isn't an array (malformed input, or a client
+    /// that never sends one) falls back to the ordinary
+    /// [`read_frame`](Self::read_frame) decode path. Returns `None` at a
+    /// clean end of stream.
+    pub async fn read_command(&mut self) -> Result<Option<FrameOwned>> {
+        let mut buf = self.framed.read_buffer_mut().split();
+
+        while buf.is_empty() {
+            if self.framed.get_mut().read_buf(&mut buf).await? == 0 {
+                self.restore(buf);
+                return Ok(None);
+            }
+        }
+
+        if buf[0] != b'*' {
+            self.restore(buf);
+            return self.read_frame().await;
+        }
+
+        let count = loop {
+            match parse_array_header(&buf)? {
+                Some((count, consumed)) => {
+                    buf.advance(consumed);
+                    break count;
+                }
+                None => {
+                    if self.framed.get_mut().read_buf(&mut buf).await? == 0 {
+                        return Err(MiniRedisError::ConnectionReset);
+                    }
                 }
-                Err(MiniRedisError::Incomplete) => (None, 0),
-                Err(e) => return Err(e),
             }
         };
 
-        if let Some(frame) = frame {
-            self.buffer.advance(consumed);
-            return Ok(Some(frame));
+        let mut elements = Vec::with_capacity(count);
+        for _ in 0..count {
+            while buf.is_empty() {
+                if self.framed.get_mut().read_buf(&mut buf).await? == 0 {
+                    return Err(MiniRedisError::ConnectionReset);
+                }
+            }
+            if buf[0] != b'$' {
+                return Err(MiniRedisError::Protocol(format!(
+                    "expected bulk string, got type {}",
+                    buf[0] as char
+                )));
+            }
+
+            let (len, consumed) = loop {
+                match parse_bulk_header(&buf)? {
+                    Some(header) => break header,
+                    None => {
+                        if self.framed.get_mut().read_buf(&mut buf).await? == 0 {
+                            return Err(MiniRedisError::ConnectionReset);
+                        }
+                    }
+                }
+            };
+            buf.advance(consumed);
+
+            let bytes = match len {
+                None => None,
+                Some(len) if self.should_stream_bulk(len) => {
+                    let mut out = Vec::with_capacity(len);
+                    self.read_bulk_body(&mut buf, len, &mut out).await?;
+                    Some(Bytes::from(out))
+                }
+                Some(len) => {
+                    while buf.len() < len + 2 {
+                        if self.framed.get_mut().read_buf(&mut buf).await? == 0 {
+                            return Err(MiniRedisError::ConnectionReset);
+                        }
+                    }
+                    let data = Bytes::copy_from_slice(&buf[..len]);
+                    buf.advance(len + 2);
+                    Some(data)
+                }
+            };
+
+            elements.push(FrameOwned::BulkString(bytes));
         }
 
-        Ok(None)
+        self.restore(buf);
+        Ok(Some(FrameOwned::Array(Some(elements))))
     }
 
-    pub async fn write_frame(&mut self, frame: &FrameOwned) -> Result<()> {
-        match frame {
-            FrameOwned::SimpleString(s) => {
-                self.stream.write_u8(b'+').await?;
-                self.stream.write_all(s.as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
-            }
-            FrameOwned::Error(s) => {
-                self.stream.write_u8(b'-').await?;
-                self.stream.write_all(s.as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
-            }
-            FrameOwned::Integer(i) => {
-                self.stream.write_u8(b':').await?;
-                self.stream.write_all(i.to_string().as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
-            }
-            FrameOwned::BulkString(None) => {
-                self.stream.write_all(b"$-1\r\n").await?;
-            }
-            FrameOwned::BulkString(Some(data)) => {
-                self.stream.write_u8(b'$').await?;
-                self.stream.write_all(data.len().to_string().as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
-                self.stream.write_all(data).await?;
-                self.stream.write_all(b"\r\n").await?;
-            }
-            FrameOwned::Array(None) => {
-                self.stream.write_all(b"*-1\r\n").await?;
-            }
-            FrameOwned::Array(Some(frames)) => {
-                self.stream.write_u8(b'*').await?;
-                self.stream.write_all(frames.len().to_string().as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
-                for frame in frames {
-                    Box::pin(self.write_frame(frame)).await?; 
+    /// Return bytes read past a streamed value to the codec's buffer so they
+    /// are not lost.
+    fn restore(&mut self, leftover: BytesMut) {
+        if !leftover.is_empty() {
+            self.framed.read_buffer_mut().unsplit(leftover);
+        }
+    }
+}
+
+impl FrameSink for Connection {
+    async fn write_frame(&mut self, frame: &FrameOwned) -> Result<()> {
+        Connection::write_frame(self, frame).await
+    }
+
+    /// Stream values past the threshold straight from memory to the socket in
+    /// [`BULK_CHUNK_SIZE`] chunks rather than cloning them into a frame.
+    async fn write_bulk_value(&mut self, value: &[u8]) -> Result<()> {
+        if self.should_stream_bulk(value.len()) {
+            let mut reader = value;
+            self.write_bulk_stream(&mut reader, value.len()).await
+        } else {
+            self.write_frame(&FrameOwned::BulkString(Some(Bytes::copy_from_slice(value))))
+                .await
+        }
+    }
+
+    fn protocol(&self) -> u8 {
+        Connection::protocol(self)
+    }
+
+    fn set_protocol(&mut self, version: u8) {
+        Connection::set_protocol(self, version);
+    }
+}
+
+/// A RESP connection carried over a WebSocket, for browser or firewalled
+/// clients that can only open a WebSocket (e.g. behind a relay/tunnel). Each
+/// [`FrameOwned`] is framed as a single binary message, but a RESP frame may
+/// still arrive split across several binary messages — the decode side
+/// accumulates payload in [`read_buffer`](Self::read_buffer) and runs
+/// `parse::check`/`parse::parse` over it exactly as the TCP path does.
+pub struct WsConnection {
+    ws: WebSocketStream<TcpStream>,
+    read_buffer: BytesMut,
+    protocol: u8,
+}
+
+impl WsConnection {
+    /// Accept the HTTP upgrade on an already-connected socket and wrap the
+    /// resulting WebSocket as a RESP transport.
+    pub async fn accept(socket: TcpStream) -> Result<Self> {
+        let ws = tokio_tungstenite::accept_async(socket)
+            .await
+            .map_err(ws_err)?;
+        Ok(Self {
+            ws,
+            read_buffer: BytesMut::new(),
+            protocol: RESP2,
+        })
+    }
+
+    /// Read the next frame, returning `None` once the peer closes the socket.
+    pub async fn read_frame(&mut self) -> Result<Option<FrameOwned>> {
+        loop {
+            // Try to carve a complete frame out of what we have buffered so far;
+            // a single binary message may hold several pipelined frames.
+            match parse::check(&self.read_buffer) {
+                Ok(()) => {
+                    let (frame, consumed) = parse::parse(&self.read_buffer)?;
+                    let owned = FrameOwned::from(frame);
+                    self.read_buffer.advance(consumed);
+                    return Ok(Some(owned));
+                }
+                Err(MiniRedisError::Incomplete) => {}
+                Err(e) => return Err(e),
+            }
+
+            // Not enough bytes yet: pull the next binary message and append it.
+            match self.ws.next().await {
+                Some(msg) => match msg.map_err(ws_err)? {
+                    Message::Binary(data) => self.read_buffer.extend_from_slice(&data),
+                    Message::Close(_) => return Ok(None),
+                    Message::Ping(_) | Message::Pong(_) => {}
+                    Message::Text(_) => {
+                        return Err(MiniRedisError::Protocol(
+                            "expected binary websocket message, got text".into(),
+                        ));
+                    }
+                    _ => {}
+                },
+                None => {
+                    if self.read_buffer.is_empty() {
+                        return Ok(None);
+                    }
+                    return Err(MiniRedisError::ConnectionReset);
                 }
             }
         }
-        self.stream.flush().await?;
+    }
+}
+
+impl FrameSink for WsConnection {
+    async fn write_frame(&mut self, frame: &FrameOwned) -> Result<()> {
+        let mut dst = BytesMut::new();
+        encode_frame(frame, &mut dst, self.protocol);
+        self.ws
+            .send(Message::binary(dst.to_vec()))
+            .await
+            .map_err(ws_err)?;
         Ok(())
     }
+
+    fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    fn set_protocol(&mut self, version: u8) {
+        self.protocol = version;
+    }
+}
+
+/// Map a tungstenite error into the crate's error type.
+fn ws_err(err: tokio_tungstenite::tungstenite::Error) -> MiniRedisError {
+    MiniRedisError::WebSocket(err.to_string())
+}
+
+/// Parse a `*<count>\r\n` array header from the front of `buf`, returning the
+/// element count and the number of header bytes consumed. Yields `Ok(None)`
+/// when the header is not yet fully buffered.
+fn parse_array_header(buf: &[u8]) -> Result<Option<(usize, usize)>> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    if buf[0] != b'*' {
+        return Err(MiniRedisError::Protocol(format!(
+            "expected array, got type {}",
+            buf[0] as char
+        )));
+    }
+
+    let mut idx = 1;
+    while idx + 1 < buf.len() {
+        if buf[idx] == b'\r' && buf[idx + 1] == b'\n' {
+            let text = std::str::from_utf8(&buf[1..idx])
+                .map_err(|_| MiniRedisError::Parse("invalid utf-8 in array length".into()))?;
+            let count = text
+                .parse::<i64>()
+                .map_err(|_| MiniRedisError::Parse(format!("invalid array length: {text}")))?;
+            if count < 0 {
+                return Err(MiniRedisError::Protocol(
+                    "command array must not be null".into(),
+                ));
+            }
+            return Ok(Some((count as usize, idx + 2)));
+        }
+        idx += 1;
+    }
+    Ok(None)
+}
+
+/// Parse a `$<len>\r\n` bulk header from the front of `buf`, returning the
+/// declared length (`None` for `$-1`) and the number of header bytes consumed.
+/// Yields `Ok(None)` when the header is not yet fully buffered.
+fn parse_bulk_header(buf: &[u8]) -> Result<Option<(Option<usize>, usize)>> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    if buf[0] != b'$' {
+        return Err(MiniRedisError::Protocol(format!(
+            "expected bulk string, got type {}",
+            buf[0] as char
+        )));
+    }
+
+    let mut idx = 1;
+    while idx + 1 < buf.len() {
+        if buf[idx] == b'\r' && buf[idx + 1] == b'\n' {
+            let text = std::str::from_utf8(&buf[1..idx])
+                .map_err(|_| MiniRedisError::Parse("invalid utf-8 in bulk length".into()))?;
+            let len = text
+                .parse::<i64>()
+                .map_err(|_| MiniRedisError::Parse(format!("invalid bulk length: {text}")))?;
+            let decoded = match len {
+                -1 => None,
+                n if n >= 0 => Some(n as usize),
+                _ => return Err(MiniRedisError::Parse("invalid bulk length".into())),
+            };
+            return Ok(Some((decoded, idx + 2)));
+        }
+        idx += 1;
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// `read_command` should stream a bulk argument past the threshold in
+    /// bounded chunks rather than buffering the whole thing, while still
+    /// reassembling it into the same `FrameOwned::Array` a small command
+    /// would decode to.
+    #[tokio::test]
+    async fn test_read_command_streams_large_bulk_argument() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+
+        let value = vec![b'x'; 3 * BULK_CHUNK_SIZE + 17];
+        let client_value = value.clone();
+        let client = tokio::spawn(async move {
+            let mut socket = TcpStream::connect(addr).await.expect("connect");
+            let mut req = Vec::new();
+            req.extend_from_slice(b"*3\r\n$3\r\nSET\r\n$3\r\nfoo\r\n");
+            req.extend_from_slice(format!("${}\r\n", client_value.len()).as_bytes());
+            req.extend_from_slice(&client_value);
+            req.extend_from_slice(b"\r\n");
+            socket.write_all(&req).await.expect("write request");
+            socket.shutdown().await.expect("shutdown");
+        });
+
+        let (socket, _) = listener.accept().await.expect("accept");
+        let mut conn = Connection::new(socket);
+        conn.set_bulk_stream_threshold(1024);
+
+        let frame = conn.read_command().await.expect("read_command").expect("frame");
+        match frame {
+            FrameOwned::Array(Some(elements)) => {
+                assert_eq!(elements.len(), 3);
+                assert_eq!(format!("{:?}", elements[0]), "BulkString(Some(b\"SET\"))");
+                assert_eq!(format!("{:?}", elements[1]), "BulkString(Some(b\"foo\"))");
+                match &elements[2] {
+                    FrameOwned::BulkString(Some(bytes)) => assert_eq!(bytes.as_ref(), value.as_slice()),
+                    other => panic!("expected bulk string value, got {other:?}"),
+                }
+            }
+            other => panic!("expected command array, got {other:?}"),
+        }
+
+        // The connection should be left exactly at end of stream.
+        assert!(conn.read_command().await.expect("clean eof").is_none());
+        client.await.expect("client task");
+    }
+
+    /// A command below the threshold still round-trips through the same
+    /// entry point.
+    #[tokio::test]
+    async fn test_read_command_small_bulk_argument() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+
+        let client = tokio::spawn(async move {
+            let mut socket = TcpStream::connect(addr).await.expect("connect");
+            socket
+                .write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n")
+                .await
+                .expect("write request");
+            socket.shutdown().await.expect("shutdown");
+        });
+
+        let (socket, _) = listener.accept().await.expect("accept");
+        let mut conn = Connection::new(socket);
+
+        let frame = conn.read_command().await.expect("read_command").expect("frame");
+        assert_eq!(
+            format!("{frame:?}"),
+            "Array(Some([BulkString(Some(b\"GET\")), BulkString(Some(b\"foo\"))]))"
+        );
+
+        client.await.expect("client task");
+    }
 }
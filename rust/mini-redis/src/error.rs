@@ -19,6 +19,9 @@ pub enum MiniRedisError {
 
     #[error("Protocol error: {0}")]
     Protocol(String),
+
+    #[error("WebSocket error: {0}")]
+    WebSocket(String),
 }
 
 pub type Result<T> = std::result::Result<T, MiniRedisError>;
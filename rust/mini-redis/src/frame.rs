@@ -7,6 +7,16 @@ pub enum Frame<'a> {
     Integer(i64),
     BulkString(Option<&'a [u8]>),
     Array(Option<Vec<Frame<'a>>>),
+    // RESP3 additions.
+    Null,
+    Boolean(bool),
+    Double(f64),
+    BigNumber(&'a str),
+    VerbatimString(&'a str, &'a [u8]),
+    Map(Vec<(Frame<'a>, Frame<'a>)>),
+    Set(Vec<Frame<'a>>),
+    Push(Vec<Frame<'a>>),
+    Attribute(Vec<(Frame<'a>, Frame<'a>)>),
 }
 
 #[derive(Debug, Clone)]
@@ -16,6 +26,16 @@ pub enum FrameOwned {
     Integer(i64),
     BulkString(Option<Bytes>),
     Array(Option<Vec<FrameOwned>>),
+    // RESP3 additions.
+    Null,
+    Boolean(bool),
+    Double(f64),
+    BigNumber(String),
+    VerbatimString(String, Bytes),
+    Map(Vec<(FrameOwned, FrameOwned)>),
+    Set(Vec<FrameOwned>),
+    Push(Vec<FrameOwned>),
+    Attribute(Vec<(FrameOwned, FrameOwned)>),
 }
 
 impl<'a> From<Frame<'a>> for FrameOwned {
@@ -28,6 +48,31 @@ impl<'a> From<Frame<'a>> for FrameOwned {
             Frame::Array(opt) => {
                 FrameOwned::Array(opt.map(|vec| vec.into_iter().map(FrameOwned::from).collect()))
             }
+            Frame::Null => FrameOwned::Null,
+            Frame::Boolean(b) => FrameOwned::Boolean(b),
+            Frame::Double(d) => FrameOwned::Double(d),
+            Frame::BigNumber(s) => FrameOwned::BigNumber(s.to_string()),
+            Frame::VerbatimString(fmt, data) => {
+                FrameOwned::VerbatimString(fmt.to_string(), Bytes::copy_from_slice(data))
+            }
+            Frame::Map(pairs) => FrameOwned::Map(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (FrameOwned::from(k), FrameOwned::from(v)))
+                    .collect(),
+            ),
+            Frame::Set(items) => {
+                FrameOwned::Set(items.into_iter().map(FrameOwned::from).collect())
+            }
+            Frame::Push(items) => {
+                FrameOwned::Push(items.into_iter().map(FrameOwned::from).collect())
+            }
+            Frame::Attribute(pairs) => FrameOwned::Attribute(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (FrameOwned::from(k), FrameOwned::from(v)))
+                    .collect(),
+            ),
         }
     }
 }
@@ -35,6 +35,18 @@ impl<'a> Parser<'a> {
             b':' => Ok(Frame::Integer(self.read_number()?)),
             b'$' => self.parse_bulk_string(),
             b'*' => self.parse_array(),
+            b'_' => {
+                self.read_line()?;
+                Ok(Frame::Null)
+            }
+            b'#' => Ok(Frame::Boolean(self.read_bool()?)),
+            b',' => Ok(Frame::Double(self.read_double()?)),
+            b'(' => Ok(Frame::BigNumber(self.read_str_line()?)),
+            b'=' => self.parse_verbatim_string(),
+            b'%' => self.parse_map(),
+            b'~' => Ok(Frame::Set(self.parse_aggregate()?)),
+            b'>' => Ok(Frame::Push(self.parse_aggregate()?)),
+            b'|' => Ok(Frame::Attribute(self.parse_pairs()?)),
             byte => Err(MiniRedisError::Protocol(format!(
                 "invalid frame type: {}",
                 byte as char
@@ -65,6 +77,36 @@ impl<'a> Parser<'a> {
                     Ok(())
                 }
             },
+            b'_' => {
+                self.read_line()?;
+                Ok(())
+            }
+            b'#' | b',' | b'(' => {
+                self.read_line()?;
+                Ok(())
+            }
+            b'=' => match self.read_length()? {
+                Length::Null => Ok(()),
+                Length::Len(len) => self.skip_bulk_bytes(len),
+            },
+            b'%' | b'|' => match self.read_length()? {
+                Length::Null => Ok(()),
+                Length::Len(len) => {
+                    for _ in 0..len * 2 {
+                        self.check_frame()?;
+                    }
+                    Ok(())
+                }
+            },
+            b'~' | b'>' => match self.read_length()? {
+                Length::Null => Ok(()),
+                Length::Len(len) => {
+                    for _ in 0..len {
+                        self.check_frame()?;
+                    }
+                    Ok(())
+                }
+            },
             byte => Err(MiniRedisError::Protocol(format!(
                 "invalid frame type: {}",
                 byte as char
@@ -95,6 +137,75 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn read_bool(&mut self) -> Result<bool> {
+        match self.read_str_line()? {
+            "t" => Ok(true),
+            "f" => Ok(false),
+            other => Err(MiniRedisError::Parse(format!("invalid boolean: {other}"))),
+        }
+    }
+
+    fn read_double(&mut self) -> Result<f64> {
+        let text = self.read_str_line()?;
+        match text {
+            "inf" => Ok(f64::INFINITY),
+            "-inf" => Ok(f64::NEG_INFINITY),
+            "nan" => Ok(f64::NAN),
+            _ => text
+                .parse::<f64>()
+                .map_err(|_| MiniRedisError::Parse(format!("invalid double: {text}"))),
+        }
+    }
+
+    fn parse_verbatim_string(&mut self) -> Result<Frame<'a>> {
+        let len = match self.read_length()? {
+            Length::Null => return Ok(Frame::Null),
+            Length::Len(len) => len,
+        };
+        let bytes = self.read_bulk_bytes(len)?;
+        // A verbatim string is `<3-char format>:<payload>`.
+        if bytes.len() < 4 || bytes[3] != b':' {
+            return Err(MiniRedisError::Parse(
+                "verbatim string missing format prefix".into(),
+            ));
+        }
+        let format = std::str::from_utf8(&bytes[..3])
+            .map_err(|_| MiniRedisError::Parse("invalid verbatim format".into()))?;
+        Ok(Frame::VerbatimString(format, &bytes[4..]))
+    }
+
+    fn parse_map(&mut self) -> Result<Frame<'a>> {
+        Ok(Frame::Map(self.parse_pairs()?))
+    }
+
+    fn parse_pairs(&mut self) -> Result<Vec<(Frame<'a>, Frame<'a>)>> {
+        match self.read_length()? {
+            Length::Null => Ok(Vec::new()),
+            Length::Len(len) => {
+                let mut pairs = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.parse_frame()?;
+                    let value = self.parse_frame()?;
+                    pairs.push((key, value));
+                }
+                Ok(pairs)
+            }
+        }
+    }
+
+    fn parse_aggregate(&mut self) -> Result<Vec<Frame<'a>>> {
+        match self.read_length()? {
+            Length::Null => Ok(Vec::new()),
+            Length::Len(len) => {
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.parse_frame()?);
+                }
+                Ok(items)
+            }
+        }
+    }
+
     fn read_u8(&mut self) -> Result<u8> {
         let pos = self.cursor.position() as usize;
         let next = pos + 1;
@@ -1,4 +1,4 @@
-use crate::{db::Db, connection::Connection, command::Command};
+use crate::{db::Db, connection::{Connection, WsConnection}, command::Command};
 use tokio::net::{TcpListener, TcpStream};
 use tracing::{info, error};
 
@@ -17,9 +17,39 @@ pub async fn run(listener: TcpListener) -> crate::Result<()> {
     }
 }
 
+/// Serve the same store to clients that can only speak WebSocket (browsers,
+/// relayed/tunnelled connections): accept the HTTP upgrade, then carry RESP
+/// frames as WebSocket binary messages.
+pub async fn run_ws(listener: TcpListener) -> crate::Result<()> {
+    let db = Db::new();
+
+    loop {
+        let (socket, _) = listener.accept().await?;
+        let db = db.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = handle_ws_connection(socket, db).await {
+                error!("websocket connection error: {}", err);
+            }
+        });
+    }
+}
+
 async fn handle_connection(socket: TcpStream, db: Db) -> crate::Result<()> {
     let mut conn = Connection::new(socket);
 
+    while let Some(frame_owned) = conn.read_command().await? {
+        info!("received frame: {:?}", frame_owned);
+        let cmd = Command::from_frame_owned(frame_owned)?;
+        cmd.apply(&db, &mut conn).await?;
+    }
+
+    Ok(())
+}
+
+async fn handle_ws_connection(socket: TcpStream, db: Db) -> crate::Result<()> {
+    let mut conn = WsConnection::accept(socket).await?;
+
     while let Some(frame_owned) = conn.read_frame().await? {
         info!("received frame: {:?}", frame_owned);
         let cmd = Command::from_frame_owned(frame_owned)?;